@@ -1,20 +1,420 @@
-use anyhow::Result;
-use sui_sdk::{SuiClient, SuiClientBuilder};
+use std::{collections::HashMap, str::FromStr};
 
-// TODO: Right now, the sdk only works on mainnet
-pub async fn get_sui_client() -> Result<SuiClient> {
-    let sui_localnet = SuiClientBuilder::default()
-        .build("http://127.0.0.1:9000")
-        .await?;
+use futures::Stream;
+use sui_sdk::{SuiClient, SuiClientBuilder, types::transaction::Argument};
+use tokio::sync::broadcast;
 
-    // Sui testnet -- https://fullnode.testnet.sui.io:443
-    let sui_testnet = SuiClientBuilder::default().build_testnet().await?;
-    println!("Sui testnet version: {}", sui_testnet.api_version());
+use crate::{
+    consts::{
+        _7K_CONFIG, _7K_PACKAGE_ID, _7K_VAULT, DEVNET_RPC_URL, LOCALNET_RPC_URL, MAINNET_RPC_URL,
+        SUI_FULL_TYPE, TESTNET_RPC_URL,
+    },
+    error::{Result, SevenKError},
+    features::{
+        prices::{request_token_price, request_token_prices},
+        swap::{
+            build_batch_tx::build_batch_tx,
+            build_tx::build_tx,
+            config::{ConfigManager, ConfigOptions, ConfigSource, SharedConfigManager},
+            config_diff::ConfigDiff,
+            estimate_gas_fee::estimate_gas_fee,
+            exact_out::{
+                ExactOutQuote, GetExactOutQuoteParams, build_exact_out_tx, request_exact_out_quote,
+            },
+            execute_swap::{SwapExecution, execute_swap},
+            get_quote::{
+                DEFAULT_BUILDABLE_QUOTE_ATTEMPTS, GetQuoteParams, request_buildable_quote,
+                request_quote,
+            },
+            get_swap_history::{
+                GetSwapHistoryParams, TradingHistoryResponse, request_swap_history,
+            },
+            quote_cache::{QuoteCache, QuoteCacheOptions},
+            quote_stream::{QuoteStreamOptions, poll_quotes},
+            simulate_swap::{SwapSimulation, simulate_swap},
+        },
+    },
+    library::dex_adapter::{AdapterRegistry, DexAdapter},
+    settings::ApiSettings,
+    signer::Signer,
+    types::{
+        aggregators::QuoteResponse,
+        tx::{
+            BuildBatchTxParams, BuildExactOutTxParams, BuildTxParams, EstimateGasFeeParams,
+            ExecuteSwapParams, SimulateSwapParams,
+        },
+    },
+    utils::{
+        http::{ApiClient, HttpOptions},
+        sui::Ptb,
+    },
+};
 
-    // Sui devnet -- https://fullnode.devnet.sui.io:443
-    let sui_devnet = SuiClientBuilder::default().build_devnet().await?;
-    println!("Sui devnet version: {}", sui_devnet.api_version());
+/// Sui network the SDK talks to.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Devnet,
+    Localnet,
+    /// Any other full node, e.g. a local validator on a non-default port.
+    Custom(String),
+}
+
+impl Network {
+    pub fn rpc_url(&self) -> &str {
+        match self {
+            Network::Mainnet => MAINNET_RPC_URL,
+            Network::Testnet => TESTNET_RPC_URL,
+            Network::Devnet => DEVNET_RPC_URL,
+            Network::Localnet => LOCALNET_RPC_URL,
+            Network::Custom(url) => url.as_str(),
+        }
+    }
+
+    /// 7k contracts for this network.
+    ///
+    /// The aggregator is only deployed on mainnet, so every network starts from the mainnet
+    /// set; override it with `SevenKClientBuilder::contracts` after publishing the 7k package
+    /// to a local validator.
+    pub fn contracts(&self) -> SevenKContracts {
+        SevenKContracts::default()
+    }
+
+    /// 7k API endpoints. The APIs only serve mainnet, so this is the production set with any
+    /// environment overrides applied.
+    pub fn api_settings(&self) -> ApiSettings {
+        ApiSettings::from_env()
+    }
+}
+
+/// Accepts `mainnet`, `testnet`, `devnet`, `localnet` or a full node URL.
+impl FromStr for Network {
+    type Err = SevenKError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            "localnet" => Ok(Network::Localnet),
+            url if url.starts_with("http://") || url.starts_with("https://") => {
+                Ok(Network::Custom(url.to_string()))
+            }
+            _ => Err(SevenKError::InvalidParams(format!("unknown network {}", s))),
+        }
+    }
+}
+
+/// Object IDs of the 7k settle package, its config and its vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SevenKContracts {
+    pub package_id: String,
+    pub config: String,
+    pub vault: String,
+    /// Package that first defined the `settle` event types. Events keep this address when the
+    /// package is upgraded, so it stays the original ID while `package_id` moves on.
+    pub event_package_id: String,
+}
+
+impl Default for SevenKContracts {
+    fn default() -> Self {
+        SevenKContracts {
+            package_id: _7K_PACKAGE_ID.to_string(),
+            config: _7K_CONFIG.to_string(),
+            vault: _7K_VAULT.to_string(),
+            event_package_id: _7K_PACKAGE_ID.to_string(),
+        }
+    }
+}
+
+pub async fn get_sui_client(network: &Network) -> Result<SuiClient> {
+    Ok(SuiClientBuilder::default().build(network.rpc_url()).await?)
+}
+
+/// Single handle over the 7k APIs and the Sui RPC.
+///
+/// Owns one pooled, rate-limited HTTP client shared by every API call, the `SuiClient` used to build
+/// transactions and the `SharedConfigManager` caching the aggregator config, so one client can
+/// build swaps from many tasks at once.
+pub struct SevenKClient {
+    http: ApiClient,
+    sui: SuiClient,
+    network: Network,
+    config_manager: SharedConfigManager,
+    settings: ApiSettings,
+    quote_cache: Option<QuoteCache>,
+}
+
+impl SevenKClient {
+    pub fn builder() -> SevenKClientBuilder {
+        SevenKClientBuilder::default()
+    }
+
+    pub fn sui_client(&self) -> &SuiClient {
+        &self.sui
+    }
+
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    pub fn http_client(&self) -> &reqwest::Client {
+        self.http.inner()
+    }
+
+    pub fn api_settings(&self) -> &ApiSettings {
+        &self.settings
+    }
+
+    pub fn config_manager(&self) -> &SharedConfigManager {
+        &self.config_manager
+    }
+
+    /// Receives the per-DEX changes of every config refresh, e.g. to alert on package upgrades.
+    pub fn subscribe_config_changes(&self) -> broadcast::Receiver<ConfigDiff> {
+        self.config_manager.subscribe()
+    }
+
+    /// Where the config used to build swaps came from. `ConfigSource::Default` means the
+    /// compiled-in addresses, which miss any package upgrade since this release.
+    pub fn config_source(&self) -> ConfigSource {
+        self.config_manager.source()
+    }
+
+    /// Served from the quote cache when one is enabled.
+    pub async fn quote(&self, params: GetQuoteParams) -> Result<QuoteResponse> {
+        cached_quote(
+            &self.http,
+            &self.settings.quote_url,
+            self.quote_cache.as_ref(),
+            params,
+        )
+        .await
+    }
+
+    /// Re-quotes `params` every interval and emits the quotes that changed, see
+    /// `quote_stream::quote_stream`. Goes through the quote cache when one is enabled.
+    pub fn quote_stream<O: Into<QuoteStreamOptions>>(
+        &self,
+        params: GetQuoteParams,
+        options: O,
+    ) -> impl Stream<Item = Result<QuoteResponse>> + use<O> {
+        let http = self.http.clone();
+        let quote_url = self.settings.quote_url.clone();
+        let cache = self.quote_cache.clone();
+
+        poll_quotes(
+            move || {
+                let http = http.clone();
+                let quote_url = quote_url.clone();
+                let cache = cache.clone();
+                let params = params.clone();
+                async move { cached_quote(&http, &quote_url, cache.as_ref(), params).await }
+            },
+            options.into(),
+        )
+    }
+
+    /// The quote cache enabled with `SevenKClientBuilder::quote_cache`, e.g. to read its stats.
+    pub fn quote_cache(&self) -> Option<&QuoteCache> {
+        self.quote_cache.as_ref()
+    }
+
+    /// Quote whose every hop can be built with the registered adapters.
+    pub async fn buildable_quote(&self, params: GetQuoteParams) -> Result<QuoteResponse> {
+        request_buildable_quote(
+            &self.http,
+            &self.settings.quote_url,
+            self.config_manager.adapters(),
+            params,
+            DEFAULT_BUILDABLE_QUOTE_ATTEMPTS,
+        )
+        .await
+    }
+
+    pub async fn quote_exact_out(&self, params: GetExactOutQuoteParams) -> Result<ExactOutQuote> {
+        request_exact_out_quote(&self.http, &self.settings.quote_url, params).await
+    }
+
+    pub async fn build_swap(&self, params: BuildTxParams) -> Result<(Ptb, Option<Argument>)> {
+        let mut config_manager = self.config_manager.clone();
+        build_tx(&self.sui, &mut config_manager, params).await
+    }
+
+    pub async fn build_batch_swap(
+        &self,
+        params: BuildBatchTxParams,
+    ) -> Result<(Ptb, Vec<Argument>)> {
+        let mut config_manager = self.config_manager.clone();
+        build_batch_tx(&self.sui, &mut config_manager, params).await
+    }
+
+    pub async fn build_exact_out_swap(
+        &self,
+        params: BuildExactOutTxParams,
+    ) -> Result<(Ptb, Option<Argument>)> {
+        let mut config_manager = self.config_manager.clone();
+        build_exact_out_tx(&self.sui, &mut config_manager, params).await
+    }
+
+    pub async fn estimate_gas(&self, mut params: EstimateGasFeeParams) -> Result<f64> {
+        if params.sui_price.is_none() {
+            params.sui_price = Some(self.sui_price().await?);
+        }
+
+        let mut config_manager = self.config_manager.clone();
+        estimate_gas_fee(&self.sui, &mut config_manager, params).await
+    }
+
+    pub async fn simulate_swap(&self, params: SimulateSwapParams) -> Result<SwapSimulation> {
+        let mut config_manager = self.config_manager.clone();
+        simulate_swap(&self.sui, &mut config_manager, params).await
+    }
+
+    pub async fn execute_swap(
+        &self,
+        signer: &dyn Signer,
+        params: ExecuteSwapParams,
+    ) -> Result<SwapExecution> {
+        let mut config_manager = self.config_manager.clone();
+        execute_swap(&self.sui, &mut config_manager, signer, params).await
+    }
+
+    pub async fn prices(&self, ids: Vec<String>, vs_coin: &str) -> Result<HashMap<String, f64>> {
+        request_token_prices(&self.http, &self.settings.prices_url, ids, vs_coin).await
+    }
+
+    pub async fn price(&self, id: &str) -> Result<f64> {
+        request_token_price(&self.http, &self.settings.prices_url, id).await
+    }
+
+    pub async fn sui_price(&self) -> Result<f64> {
+        self.price(SUI_FULL_TYPE).await
+    }
+
+    pub async fn history(&self, params: GetSwapHistoryParams) -> Result<TradingHistoryResponse> {
+        request_swap_history(&self.http, &self.settings.history_url, params).await
+    }
+}
+
+async fn cached_quote(
+    http: &ApiClient,
+    quote_url: &str,
+    cache: Option<&QuoteCache>,
+    params: GetQuoteParams,
+) -> Result<QuoteResponse> {
+    match cache {
+        Some(cache) => {
+            let fetch = request_quote(http, quote_url, params.clone());
+            cache.get_or_fetch(&params, fetch).await
+        }
+        None => request_quote(http, quote_url, params).await,
+    }
+}
+
+#[derive(Default)]
+pub struct SevenKClientBuilder {
+    http: Option<reqwest::Client>,
+    http_options: HttpOptions,
+    sui: Option<SuiClient>,
+    network: Network,
+    contracts: Option<SevenKContracts>,
+    settings: Option<ApiSettings>,
+    adapters: AdapterRegistry,
+    config_options: ConfigOptions,
+    quote_cache: Option<QuoteCacheOptions>,
+}
+
+impl SevenKClientBuilder {
+    /// Reuses an existing HTTP client instead of creating a new pool.
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = Some(http);
+        self
+    }
+
+    /// Sets the timeouts, retries and rate limit of the 7k API requests.
+    pub fn http_options(mut self, options: HttpOptions) -> Self {
+        self.http_options = options;
+        self
+    }
+
+    /// Reuses an existing Sui client instead of connecting to the network's RPC endpoint.
+    pub fn sui_client(mut self, sui: SuiClient) -> Self {
+        self.sui = Some(sui);
+        self
+    }
+
+    /// Selects the RPC endpoint, the 7k contracts and the default API hosts.
+    pub fn network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Overrides the 7k contracts selected by the network.
+    pub fn contracts(mut self, contracts: SevenKContracts) -> Self {
+        self.contracts = Some(contracts);
+        self
+    }
+
+    /// Registers a DEX adapter next to the built-in ones, replacing any with the same id.
+    pub fn adapter(mut self, adapter: impl DexAdapter + 'static) -> Self {
+        self.adapters.register(adapter);
+        self
+    }
+
+    /// Sets the config TTL and the file caching it across restarts.
+    pub fn config_options(mut self, options: ConfigOptions) -> Self {
+        self.config_options = options;
+        self
+    }
+
+    /// Caches `quote` results for `options.ttl` and shares identical requests in flight.
+    pub fn quote_cache(mut self, options: QuoteCacheOptions) -> Self {
+        self.quote_cache = Some(options);
+        self
+    }
+
+    /// Overrides the API endpoints selected by the network.
+    pub fn api_settings(mut self, settings: ApiSettings) -> Self {
+        self.settings = Some(settings);
+        self
+    }
+
+    /// Sends every API request to a local stand-in serving recorded fixtures.
+    pub fn mock_server(self, base_url: &str) -> Self {
+        self.api_settings(ApiSettings::mock(base_url))
+    }
+
+    pub async fn build(self) -> Result<SevenKClient> {
+        let http = ApiClient::new(self.http.unwrap_or_default(), self.http_options);
+
+        let network = self.network;
+
+        let sui = match self.sui {
+            Some(sui) => sui,
+            None => get_sui_client(&network).await?,
+        };
+
+        let settings = self.settings.unwrap_or_else(|| network.api_settings());
+        let contracts = self.contracts.unwrap_or_else(|| network.contracts());
+        let config_manager = ConfigManager::with_options(
+            http.clone(),
+            settings.config_url.clone(),
+            self.config_options,
+        )
+        .await?
+        .with_contracts(contracts)
+        .with_adapters(self.adapters);
 
-    // TODO
-    Ok(sui_localnet)
+        Ok(SevenKClient {
+            http,
+            sui,
+            network,
+            config_manager: config_manager.into_shared(),
+            settings,
+            quote_cache: self.quote_cache.map(QuoteCache::new),
+        })
+    }
 }
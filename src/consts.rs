@@ -8,6 +8,17 @@ pub const _7K_CONFIG: &'static str =
 pub const _7K_VAULT: &'static str =
     "0x39a3c55742c0e011b6f65548e73cf589e1ae5e82dbfab449ca57f24c3bcd9514";
 
+// 7k APIs
+pub const API_URL: &'static str = "https://api.7k.ag";
+pub const PRICES_API_URL: &'static str = "https://prices.7k.ag";
+pub const STATISTIC_API_URL: &'static str = "https://statistic.7k.ag";
+
+// Sui RPC
+pub const MAINNET_RPC_URL: &'static str = "https://fullnode.mainnet.sui.io:443";
+pub const TESTNET_RPC_URL: &'static str = "https://fullnode.testnet.sui.io:443";
+pub const DEVNET_RPC_URL: &'static str = "https://fullnode.devnet.sui.io:443";
+pub const LOCALNET_RPC_URL: &'static str = "http://127.0.0.1:9000";
+
 // Explorer
 pub const EXPLORER_URI: &'static str = "https://suiscan.xyz";
 
@@ -0,0 +1,151 @@
+use sui_sdk::types::base_types::ObjectIDParseError;
+use thiserror::Error;
+
+pub type Result<T, E = SevenKError> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum SevenKError {
+    /// A 7k API answered with a non-success status.
+    #[error("HTTP {status}: {body}")]
+    Http { status: u16, body: String },
+
+    /// The request never got a response (connect error, timeout, ...).
+    #[error("request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("failed to decode response: {0}")]
+    Decode(String),
+
+    #[error("insufficient balance: needed {needed}, available {available}")]
+    InsufficientBalance { needed: u64, available: u64 },
+
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+
+    #[error("invalid params: {0}")]
+    InvalidParams(String),
+
+    #[error("invalid quote: {0}")]
+    InvalidQuote(String),
+
+    #[error("unsupported dex: {0}")]
+    UnsupportedDex(String),
+
+    #[error("rpc error: {0}")]
+    Rpc(#[from] sui_sdk::error::Error),
+
+    #[error("failed to fetch object {object_id}: {reason}")]
+    ObjectFetch { object_id: String, reason: String },
+
+    #[error("simulation failed: {message}")]
+    SimulationFailed {
+        abort_code: Option<u64>,
+        message: String,
+    },
+
+    /// The transaction was executed on chain but aborted; gas has been charged.
+    #[error("transaction {digest} failed: {message}")]
+    ExecutionFailed {
+        digest: String,
+        abort_code: Option<u64>,
+        message: String,
+    },
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl SevenKError {
+    /// Whether the same call may succeed if retried: transport failures and timeouts, rate
+    /// limiting and server-side HTTP errors.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            SevenKError::Http { status, .. } => *status == 429 || *status >= 500,
+            SevenKError::Request(err) => err.is_timeout() || err.is_connect(),
+            SevenKError::Rpc(err) => is_transient_rpc(err),
+            _ => false,
+        }
+    }
+
+    /// Builds a `SimulationFailed` from an execution status error, extracting the Move abort
+    /// code when there is one.
+    pub fn simulation_failed(message: impl Into<String>) -> Self {
+        let message = message.into();
+
+        SevenKError::SimulationFailed {
+            abort_code: parse_abort_code(&message),
+            message,
+        }
+    }
+
+    pub fn execution_failed(digest: impl Into<String>, message: impl Into<String>) -> Self {
+        let message = message.into();
+
+        SevenKError::ExecutionFailed {
+            digest: digest.into(),
+            abort_code: parse_abort_code(&message),
+            message,
+        }
+    }
+}
+
+/// Only errors where the node was not reached or did not answer in time. An error the node
+/// answered with, such as object not found, invalid params or a rejected transaction, fails the
+/// same way again.
+fn is_transient_rpc(err: &sui_sdk::error::Error) -> bool {
+    match err {
+        sui_sdk::error::Error::RpcError(_) => err.json_rpc_error().is_none(),
+        sui_sdk::error::Error::IOError(_) => true,
+        _ => false,
+    }
+}
+
+impl From<serde_json::Error> for SevenKError {
+    fn from(err: serde_json::Error) -> Self {
+        SevenKError::Decode(err.to_string())
+    }
+}
+
+impl From<ObjectIDParseError> for SevenKError {
+    fn from(err: ObjectIDParseError) -> Self {
+        SevenKError::InvalidAddress(err.to_string())
+    }
+}
+
+/// Extracts the abort code from a status such as
+/// `MoveAbort(MoveLocation { .. }, 3) in command 5`.
+fn parse_abort_code(message: &str) -> Option<u64> {
+    let start = message.find("MoveAbort(")?;
+    let rest = &message[start..];
+    let location_end = rest.find("}, ")?;
+    let code = &rest[location_end + 3..];
+    let code_end = code.find(')')?;
+
+    code[..code_end].trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_abort_code() {
+        let message = "MoveAbort(MoveLocation { module: ModuleId { address: 7ea6e27ad7af6f3b8671d59df1aaebd7c03dddab893e52a714227b2f4fe91519, name: Identifier(\"settle\") }, function: 2, instruction: 37, function_name: Some(\"settle\") }, 3) in command 5";
+
+        assert_eq!(parse_abort_code(message), Some(3));
+        assert_eq!(parse_abort_code("InsufficientGas"), None);
+    }
+
+    #[test]
+    fn test_rpc_errors_answered_by_the_node_are_not_retryable() {
+        let not_found = SevenKError::Rpc(sui_sdk::error::Error::DataError(
+            "object 0x5 not found".to_string(),
+        ));
+        let disconnected = SevenKError::Rpc(sui_sdk::error::Error::IOError(
+            std::io::ErrorKind::ConnectionReset.into(),
+        ));
+
+        assert!(!not_found.is_retryable());
+        assert!(disconnected.is_retryable());
+    }
+}
@@ -1,11 +1,14 @@
-use crate::{consts::SUI_FULL_TYPE, types::token::SuiscanToken};
-use anyhow::Result;
-use reqwest::Client;
+use crate::{
+    consts::SUI_FULL_TYPE,
+    error::{Result, SevenKError},
+    settings::ApiSettings,
+    types::token::SuiscanToken,
+    utils::http::{ApiClient, Endpoint, decode_json},
+};
 use serde::{Deserialize, Serialize};
 
 const MAX_TOTAL_IDS: usize = 500;
 const MAX_IDS_PER_REQUEST: usize = 100;
-const PRICES_API: &str = "https://prices.7k.ag";
 const NATIVE_USDC_TOKEN_TYPE: &str =
     "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
 
@@ -31,19 +34,44 @@ impl From<SuiscanToken> for TokenPrice {
 }
 
 pub async fn get_token_price(id: &str) -> Result<f64> {
-    let client = Client::new();
+    request_token_price(ApiClient::global(), &ApiSettings::from_env().prices_url, id).await
+}
+
+pub(crate) async fn request_token_price(
+    http: &ApiClient,
+    prices_url: &str,
+    id: &str,
+) -> Result<f64> {
     let url = format!(
-        "{}/price?ids={}&vsCoin={}",
-        PRICES_API, id, NATIVE_USDC_TOKEN_TYPE
+        "{}?ids={}&vsCoin={}",
+        prices_url, id, NATIVE_USDC_TOKEN_TYPE
     );
-    let response = client.get(&url).send().await?;
-    let prices_res: serde_json::Value = response.json().await?;
-    Ok(prices_res[id]["price"].as_f64().unwrap())
+    let response = http.send(Endpoint::Prices, |http| http.get(&url)).await?;
+    let prices_res: serde_json::Value = decode_json(response).await?;
+
+    prices_res[id]["price"]
+        .as_f64()
+        .ok_or_else(|| SevenKError::Decode(format!("missing price for {}", id)))
 }
 
 pub async fn get_token_prices(
     ids: Vec<String>,
     vs_coin: &str,
+) -> Result<std::collections::HashMap<String, f64>> {
+    request_token_prices(
+        ApiClient::global(),
+        &ApiSettings::from_env().prices_url,
+        ids,
+        vs_coin,
+    )
+    .await
+}
+
+pub(crate) async fn request_token_prices(
+    http: &ApiClient,
+    prices_url: &str,
+    ids: Vec<String>,
+    vs_coin: &str,
 ) -> Result<std::collections::HashMap<String, f64>> {
     let limited_ids: Vec<String> = ids.into_iter().take(MAX_TOTAL_IDS).collect();
     let id_chunks: Vec<Vec<String>> = limited_ids
@@ -51,16 +79,15 @@ pub async fn get_token_prices(
         .map(|chunk| chunk.to_vec())
         .collect();
 
-    let client = Client::new();
     let mut responses = Vec::new();
 
     for chunk in id_chunks {
-        let response = client
-            .post(&format!("{}/price", PRICES_API))
-            .json(&serde_json::json!({ "ids": chunk, "vsCoin": vs_coin }))
-            .send()
+        let body = serde_json::json!({ "ids": chunk, "vsCoin": vs_coin });
+        let response = http
+            .send(Endpoint::Prices, |http| http.post(prices_url).json(&body))
             .await?;
-        let prices_res: std::collections::HashMap<String, TokenPrice> = response.json().await?;
+        let prices_res: std::collections::HashMap<String, TokenPrice> =
+            decode_json(response).await?;
         responses.push(prices_res);
     }
 
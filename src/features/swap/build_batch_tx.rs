@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use sui_sdk::{
+    SuiClient,
+    types::{base_types::SuiAddress, transaction::Argument},
+};
+
+use crate::{
+    error::{Result, SevenKError},
+    library::{group_swap_routes::group_swap_routes, swap_with_route::route_shared_objects},
+    metrics,
+    types::{
+        aggregators::TxSorSwap,
+        tx::{BuildBatchTxParams, CommonParams},
+    },
+    utils::{
+        object_ref_cache::ObjectRefCache,
+        sui::{ArgumentExt, Ptb},
+        token::denormalize_token_type,
+    },
+};
+
+use super::{
+    build_tx::{SettleObjects, SettleParams, parse_swap_amount, route_splits, swap_and_settle},
+    config::ConfigProvider,
+};
+
+struct PreparedSwap {
+    common: CommonParams,
+    routes: Vec<Vec<TxSorSwap>>,
+    splits: Vec<u64>,
+    swap_amount: u64,
+    partner: SuiAddress,
+    input_coins: Vec<Argument>,
+}
+
+/// Builds several independent swaps into one transaction so they succeed or fail together.
+///
+/// Input coins are selected and split once per input token type, and the 7k config and vault
+/// are fetched once for all `settle` calls. Output coins are returned in the order of
+/// `params.swaps`.
+pub async fn build_batch_tx(
+    client: &SuiClient,
+    config_manager: &mut dyn ConfigProvider,
+    params: BuildBatchTxParams,
+) -> Result<(Ptb, Vec<Argument>)> {
+    let BuildBatchTxParams {
+        swaps,
+        tx,
+        dev_inspect,
+    } = params;
+
+    let account_address = match swaps.first() {
+        Some(first) => first.account_address.clone(),
+        None => return Err(SevenKError::InvalidParams("no swaps to build".to_string())),
+    };
+    let sender = SuiAddress::from_str(&account_address)
+        .map_err(|_| SevenKError::InvalidAddress(account_address.clone()))?;
+
+    let adapters = config_manager.adapters().clone();
+    let mut prepared = Vec::with_capacity(swaps.len());
+    for common in swaps {
+        if common.account_address != account_address {
+            return Err(SevenKError::InvalidParams(format!(
+                "every swap must be sent by {}, got {}",
+                account_address, common.account_address
+            )));
+        }
+        if common.extend_tx.is_some() {
+            return Err(SevenKError::InvalidParams(
+                "extend_tx is not supported in a batch, use `tx` instead".to_string(),
+            ));
+        }
+
+        let routes = group_swap_routes(&common.quote_response)?;
+        if routes.is_empty() {
+            return Err(SevenKError::InvalidQuote(format!(
+                "no routes from {} to {}",
+                common.quote_response.token_in, common.quote_response.token_out
+            )));
+        }
+        adapters.ensure_supported(&routes)?;
+
+        let partner = SuiAddress::from_str(&common.commission.partner)
+            .map_err(|_| SevenKError::InvalidAddress(common.commission.partner.clone()))?;
+
+        prepared.push(PreparedSwap {
+            splits: route_splits(&routes)?,
+            swap_amount: parse_swap_amount(&common.quote_response)?,
+            routes,
+            partner,
+            common,
+            input_coins: vec![],
+        });
+    }
+
+    let is_extended = tx.is_some();
+    let mut tx = tx.unwrap_or_else(Ptb::new);
+
+    // Swaps grouped by input token type, in order of first appearance
+    let mut groups: Vec<(String, Vec<usize>)> = vec![];
+    for (index, swap) in prepared.iter().enumerate() {
+        let coin_type = denormalize_token_type(&swap.common.quote_response.token_in);
+
+        match groups
+            .iter_mut()
+            .find(|(group_type, _)| group_type == coin_type)
+        {
+            Some((_, indices)) => indices.push(index),
+            None => groups.push((coin_type.to_string(), vec![index])),
+        }
+    }
+
+    for (coin_type, indices) in &groups {
+        let mut total = 0u64;
+        let mut splits = vec![];
+        for &index in indices {
+            total = total
+                .checked_add(prepared[index].swap_amount)
+                .ok_or_else(|| {
+                    SevenKError::InvalidParams(format!("total {} input overflows u64", coin_type))
+                })?;
+            splits.extend_from_slice(&prepared[index].splits);
+        }
+
+        let coins_arg = tx
+            .get_split_coin_for_tx(sender, total, &splits, coin_type, dev_inspect, client)
+            .await?;
+        let mut coins = coins_arg.split(splits.len() as u16)?.into_iter();
+
+        for &index in indices {
+            let count = prepared[index].splits.len();
+            prepared[index].input_coins = coins.by_ref().take(count).collect();
+        }
+    }
+
+    let contracts = config_manager.contracts().clone();
+    let config = config_manager.config().await?;
+
+    // One prefetch for the shared objects of every swap
+    let objects = ObjectRefCache::global();
+    let mut object_ids = SettleObjects::object_ids(&contracts)?;
+    for swap in &prepared {
+        object_ids.extend(route_shared_objects(&swap.routes, &config, &adapters)?);
+        metrics::record_object_fetches(&swap.routes, &config, &adapters, objects);
+    }
+    objects.prefetch(client, &object_ids).await?;
+
+    let settle_objects = SettleObjects::new(objects, &contracts)?;
+
+    let mut coins_out = Vec::with_capacity(prepared.len());
+    for swap in prepared {
+        let PreparedSwap {
+            common,
+            routes,
+            swap_amount,
+            partner,
+            input_coins,
+            ..
+        } = swap;
+
+        let min_received = common
+            .slippage
+            .min_received(&common.quote_response.return_amount_with_decimal)?;
+
+        let coin_out = swap_and_settle(
+            &mut tx,
+            &config,
+            objects,
+            &adapters,
+            &settle_objects,
+            SettleParams {
+                quote_response: &common.quote_response,
+                routes: &routes,
+                input_coins,
+                account_address: &account_address,
+                partner,
+                commission_bps: common.commission.commission_bps,
+                swap_amount,
+                min_received,
+            },
+        )?
+        .ok_or_else(|| SevenKError::InvalidQuote("quote has no routes".to_string()))?;
+
+        coins_out.push(coin_out);
+    }
+
+    if !is_extended {
+        let addy = tx.pure(sender)?;
+        tx.transfer_objects(coins_out.clone(), addy)?;
+    }
+
+    Ok((tx, coins_out))
+}
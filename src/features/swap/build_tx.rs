@@ -1,29 +1,49 @@
 use std::str::FromStr;
 
 use crate::{
-    consts::{_7K_CONFIG, _7K_PACKAGE_ID, _7K_VAULT},
+    client::SevenKContracts,
+    error::{Result, SevenKError},
     library::{
+        dex_adapter::AdapterRegistry,
         group_swap_routes::group_swap_routes,
-        swap_with_route::{ToTypeTags, swap_with_route},
+        swap_with_route::{ToTypeTags, route_shared_objects, swap_with_route},
+    },
+    metrics,
+    types::{
+        aggregators::{Config, QuoteResponse, TxSorSwap},
+        tx::BuildTxParams,
     },
-    types::tx::BuildTxParams,
     utils::{
-        sui::{ArgumentExt, ObjectRefFetcher, Ptb},
+        object_ref_cache::ObjectRefCache,
+        sui::{ArgumentExt, Ptb},
         token::denormalize_token_type,
     },
 };
-use anyhow::Result;
 use sui_sdk::{
     SuiClient,
-    types::{base_types::SuiAddress, transaction::Argument},
+    types::{
+        base_types::{ObjectID, SuiAddress},
+        transaction::{Argument, ObjectArg},
+    },
 };
 
-use super::config::ConfigManager;
+use super::config::ConfigProvider;
 
 pub async fn build_tx(
     client: &SuiClient,
-    config_manager: &mut ConfigManager,
+    config_manager: &mut dyn ConfigProvider,
     params: BuildTxParams,
+) -> Result<(Ptb, Option<Argument>)> {
+    build_tx_with_min_out(client, config_manager, params, None).await
+}
+
+/// Same as `build_tx`, with `min_amount_out` replacing the slippage-derived minimum passed to
+/// `settle` when given.
+pub(crate) async fn build_tx_with_min_out(
+    client: &SuiClient,
+    config_manager: &mut dyn ConfigProvider,
+    params: BuildTxParams,
+    min_amount_out: Option<u64>,
 ) -> Result<(Ptb, Option<Argument>)> {
     let BuildTxParams {
         common: common_params,
@@ -37,19 +57,23 @@ pub async fn build_tx(
     let extend_tx = common_params.extend_tx;
 
     if account_address.is_empty() {
-        return Err(anyhow::anyhow!("Sender address is required"));
+        return Err(SevenKError::InvalidParams(
+            "sender address is required".to_string(),
+        ));
     }
 
+    let sender = SuiAddress::from_str(account_address)
+        .map_err(|_| SevenKError::InvalidAddress(account_address.clone()))?;
+
     if quote_response.routes.is_none() {
-        return Err(anyhow::anyhow!(
-            "Invalid quote response: 'routes' are required"
+        return Err(SevenKError::InvalidQuote(
+            "'routes' are required".to_string(),
         ));
     }
 
     // Validate commission partner address
-    if !SuiAddress::from_str(&commission.partner).is_ok() {
-        return Err(anyhow::anyhow!("Invalid commission partner address"));
-    }
+    let partner_address = SuiAddress::from_str(&commission.partner)
+        .map_err(|_| SevenKError::InvalidAddress(commission.partner.clone()))?;
 
     let is_extended = extend_tx.is_some();
     let (mut tx, coin_in) = match extend_tx {
@@ -57,115 +81,335 @@ pub async fn build_tx(
         None => (Ptb::new(), None),
     };
 
+    // Reject sources without an adapter before selecting coins
+    let adapters = config_manager.adapters().clone();
     let routes = group_swap_routes(&quote_response)?;
-    let splits: Vec<u64> = routes
-        .iter()
-        .map(|group| {
-            group
-                .first()
-                .as_ref()
-                .unwrap()
-                .swap
-                .amount
-                .parse::<u64>()
-                .unwrap()
-        })
-        .collect();
+    adapters.ensure_supported(&routes)?;
+    let splits = route_splits(&routes)?;
+    let swap_amount = parse_swap_amount(&quote_response)?;
+    tracing::debug!(
+        token_in = %quote_response.token_in,
+        token_out = %quote_response.token_out,
+        amount_in = swap_amount,
+        routes = routes.len(),
+        "building swap"
+    );
+
+    let contracts = config_manager.contracts().clone();
+    let config = config_manager.config().await?;
 
     let coins_arg = if let Some(coin_in) = coin_in {
         let split_coins = tx.split_coins(coin_in, &splits)?;
         tx.transfer_or_destroy_zero_coin(
+            &config,
             quote_response.token_in.as_str(),
             coin_in,
-            Some(SuiAddress::from_str(account_address)?),
+            Some(sender),
         )?;
         split_coins
     } else {
         let split_result = tx
             .get_split_coin_for_tx(
-                SuiAddress::from_str(account_address)?,
-                quote_response.swap_amount.parse::<u64>().unwrap(),
+                sender,
+                swap_amount,
                 &splits,
                 denormalize_token_type(&quote_response.token_in),
                 dev_inspect,
+                client,
             )
             .await?;
         split_result
     };
 
-    let mut coin_objects = Vec::new();
-    let config = config_manager.get_config().await?;
+    // Resolve every shared object up front so the routes are built without RPC round trips
+    let objects = ObjectRefCache::global();
+    let mut object_ids = route_shared_objects(&routes, &config, &adapters)?;
+    object_ids.extend(SettleObjects::object_ids(&contracts)?);
+    metrics::record_object_fetches(&routes, &config, &adapters, objects);
+    objects.prefetch(client, &object_ids).await?;
+
+    let settle_objects = SettleObjects::new(objects, &contracts)?;
+
+    let min_received = match min_amount_out {
+        Some(amount) => amount,
+        None => slippage.min_received(&quote_response.return_amount_with_decimal)?,
+    };
 
-    for (index, route) in routes.iter().enumerate() {
-        let input_coin_object = coins_arg.get_slice(index as u16)?;
+    let coin_out = swap_and_settle(
+        &mut tx,
+        &config,
+        objects,
+        &adapters,
+        &settle_objects,
+        SettleParams {
+            quote_response: &quote_response,
+            routes: &routes,
+            input_coins: coins_arg.split(routes.len() as u16)?,
+            account_address,
+            partner: partner_address,
+            commission_bps: commission.commission_bps,
+            swap_amount,
+            min_received,
+        },
+    )?;
+
+    if let Some(merge_coin) = coin_out
+        && !is_extended
+    {
+        let addy = tx.pure(sender)?;
+        // Transfer objects if not an extended transaction
+        tx.transfer_objects(vec![merge_coin], addy)?;
+    }
+
+    Ok((tx, coin_out))
+}
+
+/// 7k objects taken by every `settle` call, resolved once per transaction.
+pub(crate) struct SettleObjects {
+    package_id: String,
+    config: ObjectArg,
+    vault: ObjectArg,
+}
+
+impl SettleObjects {
+    pub(crate) fn object_ids(contracts: &SevenKContracts) -> Result<Vec<ObjectID>> {
+        Ok(vec![
+            ObjectID::from_str(&contracts.config)?,
+            ObjectID::from_str(&contracts.vault)?,
+        ])
+    }
+
+    /// Expects `object_ids` to be prefetched into `objects`.
+    pub(crate) fn new(objects: &ObjectRefCache, contracts: &SevenKContracts) -> Result<Self> {
+        Ok(SettleObjects {
+            package_id: contracts.package_id.clone(),
+            config: objects.cached_shared_obj(&contracts.config, true)?,
+            vault: objects.cached_shared_obj(&contracts.vault, true)?,
+        })
+    }
+}
+
+pub(crate) struct SettleParams<'a> {
+    pub quote_response: &'a QuoteResponse,
+    pub routes: &'a [Vec<TxSorSwap>],
+    /// One coin per route, already split to the route amount.
+    pub input_coins: Vec<Argument>,
+    pub account_address: &'a String,
+    pub partner: SuiAddress,
+    pub commission_bps: u16,
+    pub swap_amount: u64,
+    pub min_received: u64,
+}
+
+/// Runs every route of a quote, merges the outputs and settles them. Returns the merged output
+/// coin, or `None` when the quote has no routes.
+pub(crate) fn swap_and_settle(
+    tx: &mut Ptb,
+    config: &Config,
+    objects: &ObjectRefCache,
+    adapters: &AdapterRegistry,
+    settle_objects: &SettleObjects,
+    mut params: SettleParams<'_>,
+) -> Result<Option<Argument>> {
+    let input_coins = std::mem::take(&mut params.input_coins);
 
+    let mut coin_objects = Vec::new();
+    for (route, input_coin_object) in params.routes.iter().zip(input_coins) {
         let coin_res = swap_with_route(
-            client,
             route,
             input_coin_object,
-            account_address,
-            &config,
-            &mut tx,
-        )
-        .await?;
+            params.account_address,
+            config,
+            objects,
+            adapters,
+            tx,
+        )?;
 
         coin_objects.push(coin_res);
     }
 
-    let mut coin_out = None;
-    if !coin_objects.is_empty() {
-        let merge_coin = if coin_objects.len() > 1 {
-            tx.merge_coins(coin_objects)
-        } else {
-            coin_objects[0].clone()
-        };
+    if coin_objects.is_empty() {
+        return Ok(None);
+    }
 
-        coin_out = Some(merge_coin.clone());
+    let merge_coin = if coin_objects.len() > 1 {
+        tx.merge_coins(coin_objects)
+    } else {
+        coin_objects[0]
+    };
 
-        let min_received =
-            ((1.0 - slippage) * quote_response.return_amount.parse::<f64>()?).round() as u64;
+    settle(tx, settle_objects, merge_coin, &params)?;
 
-        let partner_addy = tx.pure(commission.partner.clone())?;
+    Ok(Some(merge_coin))
+}
 
-        let partner = tx.move_call(
-            "0x1",
-            "option",
-            "some",
-            vec!["address"].to_type_tags()?,
-            vec![partner_addy],
-        )?;
+/// Calls `settle::settle` on `merge_coin`. Its amounts are u64 and the partner an address, so
+/// they are passed as such rather than as the decimal strings of the quote.
+fn settle(
+    tx: &mut Ptb,
+    settle_objects: &SettleObjects,
+    merge_coin: Argument,
+    params: &SettleParams<'_>,
+) -> Result<()> {
+    let quote_response = params.quote_response;
+    let expected_amount = quote_response
+        .return_amount_with_decimal
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid return amount {}",
+                quote_response.return_amount_with_decimal
+            ))
+        })?;
+
+    let partner_addy = tx.pure(params.partner)?;
+
+    let partner = tx.move_call(
+        "0x1",
+        "option",
+        "some",
+        vec!["address"].to_type_tags()?,
+        vec![partner_addy],
+    )?;
+
+    let args = vec![
+        tx.obj(settle_objects.config)?,
+        tx.obj(settle_objects.vault)?,
+        tx.pure(params.swap_amount)?,
+        merge_coin,
+        tx.pure(params.min_received)?,
+        tx.pure(expected_amount)?,
+        partner,
+        tx.pure(params.commission_bps as u64)?,
+    ];
+
+    tx.move_call(
+        &settle_objects.package_id,
+        "settle",
+        "settle",
+        vec![
+            quote_response.token_in.as_str(),
+            quote_response.token_out.as_str(),
+        ]
+        .to_type_tags()?,
+        args,
+    )?;
+
+    Ok(())
+}
 
-        let args = vec![
-            tx.obj(client.shared_obj_mut(_7K_CONFIG).await?)?,
-            tx.obj(client.shared_obj_mut(_7K_VAULT).await?)?,
-            tx.pure(quote_response.swap_amount_with_decimal)?,
+/// Input amount of each route, in route order.
+pub(crate) fn route_splits(routes: &[Vec<TxSorSwap>]) -> Result<Vec<u64>> {
+    routes
+        .iter()
+        .map(|group| {
+            let amount = &group
+                .first()
+                .ok_or_else(|| SevenKError::InvalidQuote("empty route".to_string()))?
+                .swap
+                .amount;
+
+            amount
+                .parse::<u64>()
+                .map_err(|_| SevenKError::InvalidQuote(format!("invalid swap amount {}", amount)))
+        })
+        .collect()
+}
+
+pub(crate) fn parse_swap_amount(quote_response: &QuoteResponse) -> Result<u64> {
+    quote_response
+        .swap_amount_with_decimal
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid swap amount {}",
+                quote_response.swap_amount_with_decimal
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_sdk::types::{
+        base_types::SequenceNumber,
+        transaction::{CallArg, Command},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_settle_pure_arguments() {
+        let quote_response: QuoteResponse = serde_json::from_value(serde_json::json!({
+            "swapAmount": "1",
+            "returnAmount": "3.039844",
+            "swapAmountWithDecimal": "1000000000",
+            "returnAmountWithDecimal": "3039844",
+            "tokenAddresses": [],
+            "tokenIn": "0x2::sui::SUI",
+            "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+            "marketSp": "0.3288",
+            "routes": [],
+            "swaps": [],
+            "warning": ""
+        }))
+        .unwrap();
+        let shared = |byte| ObjectArg::SharedObject {
+            id: ObjectID::from_single_byte(byte),
+            initial_shared_version: SequenceNumber::from_u64(1),
+            mutable: true,
+        };
+        let settle_objects = SettleObjects {
+            package_id: "0x7".to_string(),
+            config: shared(1),
+            vault: shared(2),
+        };
+        let partner = SuiAddress::from_str(
+            "0x4d8fe2b4ea1cc4e1aa1e7a0ff4a3ac22a1d5ccf06b0ba0e5fd8eb0bcfd2d0f91",
+        )
+        .unwrap();
+        let account_address = String::new();
+
+        let mut tx = Ptb::new();
+        let merge_coin = tx.pure(0u8).unwrap();
+        settle(
+            &mut tx,
+            &settle_objects,
             merge_coin,
-            tx.pure(min_received)?,
-            tx.pure(quote_response.return_amount_with_decimal)?,
-            partner,
-            tx.pure(commission.commission_bps)?,
-        ];
-
-        tx.move_call(
-            _7K_PACKAGE_ID,
-            "settle",
-            "settle",
-            vec![
-                quote_response.token_in.as_str(),
-                quote_response.token_out.as_str(),
-            ]
-            .to_type_tags()?,
-            args,
-        )?;
+            &SettleParams {
+                quote_response: &quote_response,
+                routes: &[],
+                input_coins: vec![],
+                account_address: &account_address,
+                partner,
+                commission_bps: 25,
+                swap_amount: 1_000_000_000,
+                min_received: 3_009_446,
+            },
+        )
+        .unwrap();
+        let pt = tx.0.finish();
 
-        // Handle commission and settlement logic here
-        // This is a simplified version - actual implementation would need proper Move call handling
-        if !is_extended {
-            let addy = tx.pure(account_address)?;
-            // Transfer objects if not an extended transaction
-            tx.transfer_objects(vec![merge_coin], addy)?;
-        }
-    }
+        let Some(Command::MoveCall(call)) = pt.commands.last() else {
+            panic!("settle is not the last command");
+        };
+        let pure = |arg: &Argument| match arg {
+            Argument::Input(idx) => match &pt.inputs[*idx as usize] {
+                CallArg::Pure(bytes) => bytes.clone(),
+                other => panic!("expected a pure input, got {other:?}"),
+            },
+            other => panic!("expected an input, got {other:?}"),
+        };
 
-    Ok((tx, coin_out))
+        assert_eq!(call.function.as_str(), "settle");
+        assert_eq!(call.arguments.len(), 8);
+        assert_eq!(pure(&call.arguments[2]), 1_000_000_000u64.to_le_bytes());
+        assert_eq!(pure(&call.arguments[4]), 3_009_446u64.to_le_bytes());
+        assert_eq!(pure(&call.arguments[5]), 3_039_844u64.to_le_bytes());
+        assert_eq!(pure(&call.arguments[7]), 25u64.to_le_bytes());
+
+        let Some(Command::MoveCall(some)) = pt.commands.first() else {
+            panic!("partner option is not the first command");
+        };
+        assert_eq!(pure(&some.arguments[0]), partner.to_inner());
+    }
 }
@@ -1,61 +1,537 @@
-use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, broadcast};
+
+use crate::client::SevenKContracts;
+use crate::error::{Result, SevenKError};
+use crate::library::dex_adapter::{AdapterRegistry, DexAdapter};
+use crate::metrics;
+use crate::settings::ApiSettings;
 use crate::types::aggregators::{
     AftermathConfig, BluefinConfig, BluemoveConfig, CetusConfig, Config, DeepbookV3Config,
     DexConfig, FlowxConfig, FlowxV3Config, KriyaV3Config, ObricConfig, TurbosConfig,
 };
+use crate::utils::http::{ApiClient, Endpoint, decode_json};
 
-// static mut CONFIG: Option<Config> = None;
-// static mut CONFIG_TS: u64 = 0;
+use super::config_diff::ConfigDiff;
 
-// Seconds
-const TTL: u64 = 60;
+pub const DEFAULT_CONFIG_TTL: Duration = Duration::from_secs(60);
 
-pub struct ConfigManager {
-    config: Config,
+// Diffs kept for subscribers that fall behind
+const CONFIG_CHANGES_CAPACITY: usize = 16;
+
+/// Where the config served by a `ConfigManager` came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Fetched from `/config`.
+    Remote,
+    /// Read from the cache file, either still fresh or because `/config` was unreachable.
+    Disk,
+    /// Compiled into this SDK version; `/config` and the cache file were both unavailable, so
+    /// package upgrades since the release are missing.
+    Default,
+}
+
+/// How a `ConfigManager` caches the config.
+#[derive(Debug, Clone)]
+pub struct ConfigOptions {
+    /// Age after which the config is refreshed in the background.
+    pub ttl: Duration,
+    /// File keeping the last config fetched from `/config` across restarts.
+    pub cache_path: Option<PathBuf>,
+}
+
+impl Default for ConfigOptions {
+    fn default() -> Self {
+        ConfigOptions {
+            ttl: DEFAULT_CONFIG_TTL,
+            cache_path: None,
+        }
+    }
+}
+
+/// Builds swaps from a config that may be refreshed between calls.
+///
+/// Implemented by `ConfigManager` for single-task use and by `SharedConfigManager` for many
+/// tasks building transactions concurrently.
+#[async_trait::async_trait]
+pub trait ConfigProvider: Send + Sync {
+    async fn config(&mut self) -> Result<Arc<Config>>;
+
+    fn contracts(&self) -> &SevenKContracts;
+
+    fn adapters(&self) -> &AdapterRegistry;
+}
+
+#[derive(Clone)]
+struct ConfigState {
+    config: Arc<Config>,
+    source: ConfigSource,
     ts: u64,
 }
 
+impl ConfigState {
+    fn is_stale(&self, ttl: Duration, now: u64) -> bool {
+        now.saturating_sub(self.ts) > ttl.as_secs()
+    }
+
+    fn fetched(config: Config) -> Self {
+        ConfigState {
+            config: Arc::new(config),
+            source: ConfigSource::Remote,
+            ts: now_secs(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedConfig {
+    fetched_at: u64,
+    config: Config,
+}
+
+/// Config state and the refresh logic behind both `ConfigManager` and `SharedConfigManager`.
+struct ConfigStore {
+    http: ApiClient,
+    config_url: String,
+    options: ConfigOptions,
+    state: RwLock<ConfigState>,
+    // Held for the duration of a `/config` request
+    refresh: Arc<Mutex<()>>,
+    refresh_started_at: AtomicU64,
+    changes: broadcast::Sender<ConfigDiff>,
+}
+
+impl ConfigStore {
+    /// Starts from the cache file when it is younger than the TTL, otherwise from `/config`,
+    /// falling back to a stale cache file and then to the compiled-in defaults.
+    async fn load(http: ApiClient, config_url: String, options: ConfigOptions) -> Self {
+        let cached = match &options.cache_path {
+            Some(path) => read_cache(path).await,
+            None => None,
+        };
+
+        let (config, source, ts) = match cached {
+            Some(cached)
+                if now_secs().saturating_sub(cached.fetched_at) <= options.ttl.as_secs() =>
+            {
+                (cached.config, ConfigSource::Disk, cached.fetched_at)
+            }
+            cached => match request_config(&http, &config_url).await {
+                Ok(config) => {
+                    if let Some(path) = &options.cache_path {
+                        save_cache(path, &config).await;
+                    }
+                    (config, ConfigSource::Remote, now_secs())
+                }
+                Err(err) => {
+                    let fallback = match cached {
+                        Some(cached) => (cached.config, ConfigSource::Disk, cached.fetched_at),
+                        None => (get_default_config(), ConfigSource::Default, 0),
+                    };
+                    tracing::warn!(error = %err, source = ?fallback.1, "falling back from /config");
+                    metrics::record_config_fallback(match fallback.1 {
+                        ConfigSource::Disk => "disk",
+                        _ => "default",
+                    });
+                    fallback
+                }
+            },
+        };
+
+        ConfigStore {
+            http,
+            config_url,
+            options,
+            state: RwLock::new(ConfigState {
+                config: Arc::new(config),
+                source,
+                ts,
+            }),
+            refresh: Arc::new(Mutex::new(())),
+            refresh_started_at: AtomicU64::new(0),
+            changes: broadcast::channel(CONFIG_CHANGES_CAPACITY).0,
+        }
+    }
+
+    fn state(&self) -> ConfigState {
+        self.state
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Returns the current config, starting a background refresh when it is past the TTL and
+    /// none is running. Outside a Tokio runtime the stale config is served without a refresh.
+    fn get_config(self: &Arc<Self>) -> Arc<Config> {
+        let state = self.state();
+        let ttl = self.options.ttl;
+        let now = now_secs();
+
+        let started_at = self.refresh_started_at.load(Ordering::Relaxed);
+        if state.is_stale(ttl, now)
+            && now.saturating_sub(started_at) > ttl.as_secs()
+            && let Ok(guard) = self.refresh.clone().try_lock_owned()
+        {
+            self.refresh_started_at.store(now, Ordering::Relaxed);
+
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    let store = self.clone();
+                    runtime.spawn(async move {
+                        let _guard = guard;
+                        if let Err(err) = store.fetch().await {
+                            tracing::warn!(error = %err, "background config refresh failed");
+                        }
+                    });
+                }
+                Err(_) => {
+                    tracing::warn!("config is stale but no Tokio runtime is running to refresh it")
+                }
+            }
+        }
+
+        state.config
+    }
+
+    /// Fetches `/config` now, or waits for the request already in flight and returns its
+    /// result.
+    async fn refresh(&self) -> Result<Arc<Config>> {
+        let before = self.state().ts;
+        let _guard = self.refresh.lock().await;
+
+        let state = self.state();
+        if state.ts != before && state.source == ConfigSource::Remote {
+            return Ok(state.config);
+        }
+
+        self.fetch().await
+    }
+
+    /// The only place a fetched config is applied: cached to disk, swapped in and published.
+    async fn fetch(&self) -> Result<Arc<Config>> {
+        let config = request_config(&self.http, &self.config_url).await?;
+        if let Some(path) = &self.options.cache_path {
+            save_cache(path, &config).await;
+        }
+
+        let state = ConfigState::fetched(config);
+        let config = state.config.clone();
+        let previous = std::mem::replace(
+            &mut *self.state.write().unwrap_or_else(|err| err.into_inner()),
+            state,
+        );
+        publish_diff(&self.changes, &previous.config, &config);
+
+        Ok(config)
+    }
+}
+
+/// Config for a single task, and the builder of a `SharedConfigManager`.
+///
+/// Both share the same refresh logic; this one only adds mutable access to the contracts and
+/// adapters before it is shared.
+pub struct ConfigManager {
+    store: Arc<ConfigStore>,
+    contracts: SevenKContracts,
+    adapters: AdapterRegistry,
+    // Last config returned by `get_config`, so it can be borrowed
+    current: Arc<Config>,
+}
+
 impl ConfigManager {
     pub async fn new() -> Result<Self> {
+        Self::with_client(
+            ApiClient::global().clone(),
+            ApiSettings::from_env().config_url,
+        )
+        .await
+    }
+
+    /// Creates a manager that fetches the config from `config_url` through a shared HTTP client.
+    pub async fn with_client(
+        http: impl Into<ApiClient>,
+        config_url: impl Into<String>,
+    ) -> Result<Self> {
+        Self::with_options(http, config_url, ConfigOptions::default()).await
+    }
+
+    /// Starts from the cache file when it is younger than the TTL, otherwise from `/config`.
+    /// When `/config` fails it falls back to a stale cache file and then to the compiled-in
+    /// defaults; check `source()` to tell them apart.
+    pub async fn with_options(
+        http: impl Into<ApiClient>,
+        config_url: impl Into<String>,
+        options: ConfigOptions,
+    ) -> Result<Self> {
+        let store = ConfigStore::load(http.into(), config_url.into(), options).await;
+        let current = store.state().config;
+
         Ok(ConfigManager {
-            config: get_refreshed_config().await?,
-            ts: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs(),
+            store: Arc::new(store),
+            contracts: SevenKContracts::default(),
+            adapters: AdapterRegistry::default(),
+            current,
         })
     }
 
+    /// Settles swaps against `contracts` instead of the mainnet 7k deployment.
+    pub fn with_contracts(mut self, contracts: SevenKContracts) -> Self {
+        self.contracts = contracts;
+        self
+    }
+
+    pub fn contracts(&self) -> &SevenKContracts {
+        &self.contracts
+    }
+
+    /// Builds swaps with `adapters` instead of the built-in set.
+    pub fn with_adapters(mut self, adapters: AdapterRegistry) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
+    pub fn adapters(&self) -> &AdapterRegistry {
+        &self.adapters
+    }
+
+    /// Adds a DEX adapter, or replaces the one registered for the same pool type.
+    pub fn register_adapter(&mut self, adapter: impl DexAdapter + 'static) {
+        self.adapters.register(adapter);
+    }
+
+    /// Returns the current config without waiting on the network.
+    ///
+    /// Past the TTL the config keeps being served while a background task fetches `/config`;
+    /// a later call picks up the result. A failed refresh keeps the current config and is
+    /// retried after another TTL.
     pub async fn get_config(&mut self) -> Result<&Config> {
-        if std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            - self.ts
-            > TTL
-        {
-            self.config = get_refreshed_config().await?;
-            self.ts = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+        self.current = self.store.get_config();
+        Ok(&self.current)
+    }
+
+    /// Fetches `/config` now, or waits for the refresh in flight, failing instead of keeping
+    /// the current config.
+    pub async fn refresh(&mut self) -> Result<&Config> {
+        self.current = self.store.refresh().await?;
+        Ok(&self.current)
+    }
+
+    /// Receives a `ConfigDiff` every time a refresh changes the config.
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigDiff> {
+        self.store.changes.subscribe()
+    }
+
+    pub fn source(&self) -> ConfigSource {
+        self.store.state().source
+    }
+
+    /// Whether swaps are built with the compiled-in addresses.
+    pub fn is_default(&self) -> bool {
+        self.source() == ConfigSource::Default
+    }
+
+    pub fn options(&self) -> &ConfigOptions {
+        &self.store.options
+    }
+
+    /// When the current config was fetched, in seconds since the epoch; 0 for the defaults.
+    pub fn get_ts(&self) -> u64 {
+        self.store.state().ts
+    }
+
+    /// Shares this manager between tasks.
+    pub fn into_shared(self) -> SharedConfigManager {
+        SharedConfigManager {
+            inner: Arc::new(SharedInner {
+                store: self.store,
+                contracts: self.contracts,
+                adapters: self.adapters,
+            }),
         }
-        Ok(&self.config)
+    }
+}
+
+#[async_trait::async_trait]
+impl ConfigProvider for ConfigManager {
+    async fn config(&mut self) -> Result<Arc<Config>> {
+        self.get_config().await?;
+        Ok(self.current.clone())
+    }
+
+    fn contracts(&self) -> &SevenKContracts {
+        &self.contracts
+    }
+
+    fn adapters(&self) -> &AdapterRegistry {
+        &self.adapters
+    }
+}
+
+/// `ConfigManager` that any number of tasks can read concurrently.
+///
+/// Clones share one config. Refreshes are single-flight: a stale config triggers at most one
+/// background `/config` request, and concurrent `refresh` calls wait for the one in progress
+/// instead of issuing their own.
+#[derive(Clone)]
+pub struct SharedConfigManager {
+    inner: Arc<SharedInner>,
+}
+
+struct SharedInner {
+    store: Arc<ConfigStore>,
+    contracts: SevenKContracts,
+    adapters: AdapterRegistry,
+}
+
+impl SharedConfigManager {
+    pub async fn new() -> Result<Self> {
+        Ok(ConfigManager::new().await?.into_shared())
+    }
+
+    /// Returns the current config without waiting on the network, starting a background
+    /// refresh when it is past the TTL and none is running. Called outside a Tokio runtime it
+    /// keeps serving the stale config; use `refresh` from async code to update it.
+    pub fn get_config(&self) -> Arc<Config> {
+        self.inner.store.get_config()
+    }
+
+    /// Fetches `/config` now, or waits for the request already in flight and returns its
+    /// result.
+    pub async fn refresh(&self) -> Result<Arc<Config>> {
+        self.inner.store.refresh().await
+    }
+
+    /// Receives a `ConfigDiff` every time a refresh changes the config.
+    pub fn subscribe(&self) -> broadcast::Receiver<ConfigDiff> {
+        self.inner.store.changes.subscribe()
+    }
+
+    pub fn contracts(&self) -> &SevenKContracts {
+        &self.inner.contracts
+    }
+
+    pub fn adapters(&self) -> &AdapterRegistry {
+        &self.inner.adapters
+    }
+
+    pub fn source(&self) -> ConfigSource {
+        self.inner.store.state().source
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.source() == ConfigSource::Default
     }
 
     pub fn get_ts(&self) -> u64 {
-        self.ts
+        self.inner.store.state().ts
     }
 }
 
+impl From<ConfigManager> for SharedConfigManager {
+    fn from(manager: ConfigManager) -> Self {
+        manager.into_shared()
+    }
+}
+
+#[async_trait::async_trait]
+impl ConfigProvider for SharedConfigManager {
+    async fn config(&mut self) -> Result<Arc<Config>> {
+        Ok(self.get_config())
+    }
+
+    fn contracts(&self) -> &SevenKContracts {
+        &self.inner.contracts
+    }
+
+    fn adapters(&self) -> &AdapterRegistry {
+        &self.inner.adapters
+    }
+}
+
+/// Fetches `/config`, failing instead of falling back to the compiled-in defaults.
 pub async fn get_refreshed_config() -> Result<Config> {
-    let client = reqwest::Client::new();
-    match client.get("https://api.7k.ag/config").send().await {
-        Ok(response) => match response.json::<Config>().await {
-            Ok(config) => Ok(config),
-            Err(_) => Ok(get_default_config()),
-        },
-        Err(_) => Ok(get_default_config()),
+    request_config(ApiClient::global(), &ApiSettings::from_env().config_url).await
+}
+
+#[tracing::instrument(skip(http))]
+pub(crate) async fn request_config(http: &ApiClient, config_url: &str) -> Result<Config> {
+    let started = Instant::now();
+    let result = match http
+        .send(Endpoint::Config, |http| http.get(config_url))
+        .await
+    {
+        Ok(response) => decode_json::<Config>(response).await,
+        Err(err) => Err(err),
+    };
+    metrics::record_config_refresh(&result);
+    let config = result?;
+
+    tracing::debug!(
+        latency_ms = started.elapsed().as_millis() as u64,
+        "config fetched"
+    );
+
+    Ok(config)
+}
+
+/// Addresses compiled into this SDK version, used when `/config` cannot be reached.
+pub fn default_config() -> Config {
+    get_default_config()
+}
+
+fn publish_diff(changes: &broadcast::Sender<ConfigDiff>, old: &Config, new: &Config) {
+    if let Ok(diff) = ConfigDiff::between(old, new)
+        && !diff.is_empty()
+    {
+        // No subscribers is not an error
+        changes.send(diff).ok();
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+async fn read_cache(path: &Path) -> Option<CachedConfig> {
+    let bytes = tokio::fs::read(path).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Writes through a temporary file so a crash never leaves a truncated cache.
+async fn write_cache(path: &Path, config: &Config) -> Result<()> {
+    let cached = CachedConfig {
+        fetched_at: now_secs(),
+        config: config.clone(),
+    };
+    let io_error = |err: std::io::Error| SevenKError::Other(err.into());
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+    }
+
+    let tmp_path = path.with_extension("tmp");
+    tokio::fs::write(&tmp_path, serde_json::to_vec(&cached)?)
+        .await
+        .map_err(io_error)?;
+    tokio::fs::rename(&tmp_path, path).await.map_err(io_error)?;
+
+    Ok(())
+}
+
+/// Failing to cache only costs a `/config` request after a restart, so it is logged instead of
+/// failing the refresh.
+async fn save_cache(path: &Path, config: &Config) {
+    if let Err(err) = write_cache(path, config).await {
+        tracing::warn!(path = %path.display(), error = %err, "failed to write config cache");
     }
 }
 
@@ -247,3 +723,86 @@ fn get_default_config() -> Config {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http::HttpOptions;
+
+    // Nothing listens on the discard port, so `/config` fails fast
+    const UNREACHABLE_URL: &str = "http://127.0.0.1:9/config";
+
+    /// Fails on the first error instead of backing off between retries.
+    fn no_retry_client() -> ApiClient {
+        ApiClient::new(
+            reqwest::Client::new(),
+            HttpOptions {
+                max_retries: 0,
+                rate_limit: None,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_cache_then_defaults() {
+        let cache_path =
+            std::env::temp_dir().join(format!("sevenk-config-{}.json", std::process::id()));
+        let options = ConfigOptions {
+            ttl: Duration::ZERO,
+            cache_path: Some(cache_path.clone()),
+        };
+
+        let manager =
+            ConfigManager::with_options(no_retry_client(), UNREACHABLE_URL, options.clone())
+                .await
+                .unwrap();
+        assert!(manager.is_default());
+
+        let mut config = default_config();
+        config.cetus.base.package = "0xcafe".to_string();
+        write_cache(&cache_path, &config).await.unwrap();
+
+        let manager = ConfigManager::with_options(no_retry_client(), UNREACHABLE_URL, options)
+            .await
+            .unwrap();
+        assert_eq!(manager.source(), ConfigSource::Disk);
+        assert_eq!(manager.current.cetus.base.package, "0xcafe");
+
+        tokio::fs::remove_file(&cache_path).await.ok();
+    }
+
+    #[tokio::test]
+    async fn test_shared_manager_keeps_config_on_failed_refresh() {
+        let manager = ConfigManager::with_client(no_retry_client(), UNREACHABLE_URL)
+            .await
+            .unwrap()
+            .into_shared();
+        let clone = manager.clone();
+
+        assert!(clone.refresh().await.is_err());
+        assert!(manager.is_default());
+        assert!(Arc::ptr_eq(&manager.get_config(), &clone.get_config()));
+    }
+
+    #[test]
+    fn test_stale_config_outside_runtime_is_served() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let manager = runtime
+            .block_on(ConfigManager::with_options(
+                no_retry_client(),
+                UNREACHABLE_URL,
+                ConfigOptions {
+                    ttl: Duration::ZERO,
+                    cache_path: None,
+                },
+            ))
+            .unwrap()
+            .into_shared();
+
+        let config = manager.get_config();
+
+        assert!(manager.is_default());
+        assert!(Arc::ptr_eq(&config, &manager.get_config()));
+    }
+}
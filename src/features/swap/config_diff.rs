@@ -0,0 +1,113 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{error::Result, types::aggregators::Config};
+
+/// One field of one DEX that differs between two configs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigChange {
+    /// Key of the DEX in `Config`, e.g. `cetus`.
+    pub dex: String,
+    /// Dotted path of the field inside the DEX config, e.g. `base.package`.
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Every field that changed between two configs, ordered by DEX and field.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConfigDiff {
+    pub changes: Vec<ConfigChange>,
+}
+
+impl ConfigDiff {
+    pub fn between(old: &Config, new: &Config) -> Result<Self> {
+        let mut changes = vec![];
+        diff_values(
+            "",
+            &serde_json::to_value(old)?,
+            &serde_json::to_value(new)?,
+            &mut changes,
+        );
+        changes.sort_by(|a, b| (&a.dex, &a.field).cmp(&(&b.dex, &b.field)));
+
+        Ok(ConfigDiff { changes })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// DEXes with at least one change, in order and without duplicates.
+    pub fn dexes(&self) -> Vec<&str> {
+        let mut dexes: Vec<&str> = self.changes.iter().map(|c| c.dex.as_str()).collect();
+        dexes.dedup();
+        dexes
+    }
+}
+
+fn diff_values(path: &str, old: &Value, new: &Value, changes: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys = old
+                .keys()
+                .chain(new.keys().filter(|key| !old.contains_key(*key)));
+
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+
+                diff_values(
+                    &path,
+                    old.get(key).unwrap_or(&Value::Null),
+                    new.get(key).unwrap_or(&Value::Null),
+                    changes,
+                );
+            }
+        }
+        (old, new) if old != new => {
+            let (dex, field) = path.split_once('.').unwrap_or((path, ""));
+
+            changes.push(ConfigChange {
+                dex: dex.to_string(),
+                field: field.to_string(),
+                old: old.clone(),
+                new: new.clone(),
+            });
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::swap::config::default_config;
+
+    #[test]
+    fn test_config_diff() {
+        let old = default_config();
+        let mut new = old.clone();
+        new.cetus.base.package = "0xc3".to_string();
+        new.turbos.version = "0x70".to_string();
+        new.flowx.base.url = Some("https://flowx.finance".to_string());
+
+        let diff = ConfigDiff::between(&old, &new).unwrap();
+
+        assert_eq!(diff.dexes(), vec!["cetus", "flowx", "turbos"]);
+        assert_eq!(
+            diff.changes[0],
+            ConfigChange {
+                dex: "cetus".to_string(),
+                field: "base.package".to_string(),
+                old: Value::String(old.cetus.base.package.clone()),
+                new: Value::String("0xc3".to_string()),
+            }
+        );
+        assert_eq!(diff.changes[1].old, Value::Null);
+        assert!(ConfigDiff::between(&old, &old).unwrap().is_empty());
+    }
+}
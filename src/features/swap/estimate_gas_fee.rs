@@ -1,21 +1,28 @@
 use std::str::FromStr;
+use std::time::Instant;
 
-use anyhow::Result;
 use sui_sdk::SuiClient;
-use sui_sdk::rpc_types::SuiTransactionBlockEffects;
+use sui_sdk::rpc_types::{SuiExecutionStatus, SuiTransactionBlockEffects};
 use sui_sdk::types::base_types::SuiAddress;
-use sui_sdk::types::gas::GasCostSummary;
 
+use crate::error::{Result, SevenKError};
 use crate::features::prices::get_sui_price;
 use crate::features::swap::build_tx::build_tx;
 use crate::types::tx::{BuildTxParams, CommonParams, EstimateGasFeeParams};
-use crate::utils::token::format_balance;
+use crate::utils::token::balance_to_decimal;
 
-use super::config::ConfigManager;
+use super::config::ConfigProvider;
 
+const SUI_DECIMALS: u32 = 9;
+
+#[tracing::instrument(skip_all, fields(
+    token_in = %params.common.quote_response.token_in,
+    token_out = %params.common.quote_response.token_out,
+    amount_in = %params.common.quote_response.swap_amount_with_decimal,
+))]
 pub async fn estimate_gas_fee(
     client: &SuiClient,
-    config_manager: &mut ConfigManager,
+    config_manager: &mut dyn ConfigProvider,
     params: EstimateGasFeeParams,
 ) -> Result<f64> {
     let EstimateGasFeeParams { common, sui_price } = params;
@@ -28,10 +35,12 @@ pub async fn estimate_gas_fee(
     } = common;
 
     if account_address.is_empty() {
-        return Ok(0.0);
+        return Err(SevenKError::InvalidParams(
+            "sender address is required".to_string(),
+        ));
     }
 
-    let build_result = build_tx(
+    let (tx, _) = build_tx(
         client,
         config_manager,
         BuildTxParams {
@@ -45,29 +54,21 @@ pub async fn estimate_gas_fee(
             dev_inspect: Some(true),
         },
     )
-    .await;
-
-    let (tx, _) = match build_result {
-        Ok(result) => result,
-        Err(err) => {
-            println!("build tx error: {}", err);
-            return Ok(0.0);
-        }
-    };
+    .await?;
 
     let sui_price = match sui_price {
         Some(price) => price,
         None => get_sui_price().await?,
     };
 
-    let sui_decimals = 9u32;
-
     let tx_payload = tx.complete();
 
+    let started = Instant::now();
     let dev_inspect = client
         .read_api()
         .dev_inspect_transaction_block(
-            SuiAddress::from_str(&account_address)?,
+            SuiAddress::from_str(&account_address)
+                .map_err(|_| SevenKError::InvalidAddress(account_address.clone()))?,
             tx_payload,
             None,
             None,
@@ -77,17 +78,20 @@ pub async fn estimate_gas_fee(
 
     let SuiTransactionBlockEffects::V1(effects) = &dev_inspect.effects;
     // let effects: &SuiTransactionBlockEffects = &dev_inspect.effects;
-    if effects.status.is_err() {
-        return Ok(0.0);
+    if let SuiExecutionStatus::Failure { error } = &effects.status {
+        return Err(SevenKError::simulation_failed(error.as_str()));
     }
 
     // let MyEnum::SingleVariant(inner) = value;
 
-    let gas_used: &GasCostSummary = &effects.gas_used;
-    let fee = gas_used.computation_cost + gas_used.storage_cost - gas_used.storage_rebate;
-
-    let fee_balance = format_balance(fee, sui_decimals);
-    let fee_usd = (fee_balance as f64) * sui_price;
+    // The storage rebate can exceed the cost, making the net fee negative
+    let fee = effects.gas_used.net_gas_usage();
+    tracing::debug!(
+        gas_used = fee,
+        latency_ms = started.elapsed().as_millis() as u64,
+        "gas estimated"
+    );
+    let fee_sui = balance_to_decimal(fee.unsigned_abs(), SUI_DECIMALS) * fee.signum() as f64;
 
-    Ok(fee_usd)
+    Ok(fee_sui * sui_price)
 }
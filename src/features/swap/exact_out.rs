@@ -0,0 +1,318 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sui_sdk::{
+    SuiClient,
+    types::{base_types::SuiAddress, transaction::Argument},
+};
+
+use crate::{
+    error::{Result, SevenKError},
+    settings::ApiSettings,
+    types::{
+        aggregators::{QuoteResponse, SourceDex},
+        slippage::Slippage,
+        tx::{BuildExactOutTxParams, BuildTxParams, ExtendTx},
+    },
+    utils::{
+        http::ApiClient,
+        sui::{ArgumentExt, Ptb},
+        token::denormalize_token_type,
+    },
+};
+
+use super::{
+    build_tx::build_tx_with_min_out,
+    config::ConfigProvider,
+    get_quote::{GetQuoteParams, request_quote},
+};
+
+const DEFAULT_TOLERANCE_BPS: u16 = 10;
+const DEFAULT_MAX_QUOTES: u32 = 16;
+// Headroom added to rate-based guesses so the first bracket usually covers the target
+const GUESS_MARGIN_BPS: u128 = 50;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct GetExactOutQuoteParams {
+    pub token_in: String,
+    pub token_out: String,
+    pub amount_out: String,
+    pub sources: Option<Vec<SourceDex>>,
+    pub target_pools: Option<Vec<String>>,
+    pub excluded_pools: Option<Vec<String>>,
+    /// Stop searching once the input is known within this many bps. Defaults to 10.
+    pub tolerance_bps: Option<u16>,
+    /// Upper bound on quote requests for one search. Defaults to 16.
+    pub max_quotes: Option<u32>,
+}
+
+/// Smallest input found whose quote returns at least `amount_out`.
+#[derive(Debug, Clone)]
+pub struct ExactOutQuote {
+    pub quote: QuoteResponse,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub quotes_requested: u32,
+}
+
+impl ExactOutQuote {
+    /// Input cap covering `slippage` of price movement, for `BuildExactOutTxParams::max_amount_in`.
+    pub fn max_amount_in(&self, slippage: Slippage) -> u64 {
+        slippage.max_amount_in(self.amount_in)
+    }
+}
+
+pub async fn get_exact_out_quote(params: GetExactOutQuoteParams) -> Result<ExactOutQuote> {
+    request_exact_out_quote(
+        ApiClient::global(),
+        &ApiSettings::from_env().quote_url,
+        params,
+    )
+    .await
+}
+
+/// Binary search over `amount_in`: the first quote gives a rate to guess an input that covers
+/// `amount_out`, then the bracket between the best short and covering inputs is halved until it
+/// is within `tolerance_bps`.
+pub(crate) async fn request_exact_out_quote(
+    http: &ApiClient,
+    quote_url: &str,
+    params: GetExactOutQuoteParams,
+) -> Result<ExactOutQuote> {
+    let target = params.amount_out.parse::<u64>().map_err(|_| {
+        SevenKError::InvalidParams(format!("invalid amount_out {}", params.amount_out))
+    })?;
+    if target == 0 {
+        return Err(SevenKError::InvalidParams(
+            "amount_out must be positive".to_string(),
+        ));
+    }
+
+    let tolerance_bps = params.tolerance_bps.unwrap_or(DEFAULT_TOLERANCE_BPS) as u128;
+    let mut search = QuoteSearch {
+        http,
+        quote_url,
+        params: &params,
+        quotes: 0,
+        max_quotes: params.max_quotes.unwrap_or(DEFAULT_MAX_QUOTES),
+    };
+
+    // Largest input known to fall short of the target
+    let mut lo = 0u64;
+    let mut guess = target;
+
+    // Smallest input known to cover the target, with its quote
+    let (mut hi_amount, mut hi_quote) = loop {
+        let (quote, out) = search.quote(guess).await?;
+        if out >= target {
+            break (guess, quote);
+        }
+
+        lo = guess;
+        guess = if out == 0 {
+            guess.saturating_mul(1000)
+        } else {
+            next_guess(guess, out, target)
+        };
+
+        if guess <= lo {
+            return Err(SevenKError::InvalidQuote(format!(
+                "no route returns {} {}",
+                target, params.token_out
+            )));
+        }
+    };
+
+    while search.quotes < search.max_quotes {
+        let gap = (hi_amount - lo) as u128;
+        if gap <= 1 || gap * 10_000 <= hi_amount as u128 * tolerance_bps {
+            break;
+        }
+
+        let mid = lo + (hi_amount - lo) / 2;
+        let (quote, out) = search.quote(mid).await?;
+
+        if out >= target {
+            hi_amount = mid;
+            hi_quote = quote;
+        } else {
+            lo = mid;
+        }
+    }
+
+    Ok(ExactOutQuote {
+        quote: hi_quote,
+        amount_in: hi_amount,
+        amount_out: target,
+        quotes_requested: search.quotes,
+    })
+}
+
+struct QuoteSearch<'a> {
+    http: &'a ApiClient,
+    quote_url: &'a str,
+    params: &'a GetExactOutQuoteParams,
+    quotes: u32,
+    max_quotes: u32,
+}
+
+impl QuoteSearch<'_> {
+    async fn quote(&mut self, amount_in: u64) -> Result<(QuoteResponse, u64)> {
+        if self.quotes >= self.max_quotes {
+            return Err(SevenKError::InvalidQuote(format!(
+                "no input covering {} {} found within {} quotes",
+                self.params.amount_out, self.params.token_out, self.max_quotes
+            )));
+        }
+        self.quotes += 1;
+
+        let quote = request_quote(
+            self.http,
+            self.quote_url,
+            GetQuoteParams {
+                token_in: self.params.token_in.clone(),
+                token_out: self.params.token_out.clone(),
+                amount_in: amount_in.to_string(),
+                sources: self.params.sources.clone(),
+                target_pools: self.params.target_pools.clone(),
+                excluded_pools: self.params.excluded_pools.clone(),
+            },
+        )
+        .await?;
+
+        let out = quote
+            .return_amount_with_decimal
+            .parse::<u64>()
+            .map_err(|_| {
+                SevenKError::InvalidQuote(format!(
+                    "invalid return amount {}",
+                    quote.return_amount_with_decimal
+                ))
+            })?;
+
+        Ok((quote, out))
+    }
+}
+
+/// Input expected to cover `target` at the rate observed for `amount_in`, plus a margin.
+fn next_guess(amount_in: u64, amount_out: u64, target: u64) -> u64 {
+    let scaled = (amount_in as u128 * target as u128).div_ceil(amount_out as u128);
+    let guess = scaled * (10_000 + GUESS_MARGIN_BPS) / 10_000;
+
+    guess.max(amount_in as u128 + 1).min(u64::MAX as u128) as u64
+}
+
+/// Builds a swap delivering at least `amount_out` while spending at most `max_amount_in`.
+///
+/// `max_amount_in` is taken from the sender (or the extended transaction's `coin_in`), the
+/// routes consume the quoted input and the remainder is sent back to the sender.
+pub async fn build_exact_out_tx(
+    client: &SuiClient,
+    config_manager: &mut dyn ConfigProvider,
+    params: BuildExactOutTxParams,
+) -> Result<(Ptb, Option<Argument>)> {
+    let BuildExactOutTxParams {
+        mut common,
+        amount_out,
+        max_amount_in,
+        dev_inspect,
+    } = params;
+
+    let quote_response = &common.quote_response;
+    let swap_amount = quote_response
+        .swap_amount_with_decimal
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid swap amount {}",
+                quote_response.swap_amount_with_decimal
+            ))
+        })?;
+    let return_amount = quote_response
+        .return_amount_with_decimal
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid return amount {}",
+                quote_response.return_amount_with_decimal
+            ))
+        })?;
+
+    if swap_amount > max_amount_in {
+        return Err(SevenKError::InvalidQuote(format!(
+            "quote spends {} which is above max_amount_in {}",
+            swap_amount, max_amount_in
+        )));
+    }
+    if return_amount < amount_out {
+        return Err(SevenKError::InvalidQuote(format!(
+            "quote returns {} which is below amount_out {}",
+            return_amount, amount_out
+        )));
+    }
+
+    let sender = SuiAddress::from_str(&common.account_address)
+        .map_err(|_| SevenKError::InvalidAddress(common.account_address.clone()))?;
+
+    let is_extended = common.extend_tx.is_some();
+    let ExtendTx { mut tx, coin_in } = common.extend_tx.take().unwrap_or(ExtendTx {
+        tx: Ptb::new(),
+        coin_in: None,
+    });
+
+    let coin_in = match coin_in {
+        Some(coin_in) => coin_in,
+        None => tx
+            .get_split_coin_for_tx(
+                sender,
+                max_amount_in,
+                &[max_amount_in],
+                denormalize_token_type(&quote_response.token_in),
+                dev_inspect,
+                client,
+            )
+            .await?
+            .get_slice(0)?,
+    };
+
+    // With a `coin_in`, build_tx splits the route amounts from it and returns the rest to the
+    // sender, which is the refund
+    common.extend_tx = Some(ExtendTx {
+        tx,
+        coin_in: Some(coin_in),
+    });
+
+    let (mut tx, coin_out) = build_tx_with_min_out(
+        client,
+        config_manager,
+        BuildTxParams {
+            common,
+            dev_inspect,
+        },
+        Some(amount_out),
+    )
+    .await?;
+
+    if !is_extended && let Some(coin_out) = coin_out {
+        let addy = tx.pure(sender)?;
+        tx.transfer_objects(vec![coin_out], addy)?;
+    }
+
+    Ok((tx, coin_out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_guess() {
+        // 1 SUI -> 3 USDC, need 30 USDC: 10 SUI plus the margin
+        assert_eq!(
+            next_guess(1_000_000_000, 3_000_000, 30_000_000),
+            10_050_000_000
+        );
+        // Always moves past the input that fell short
+        assert_eq!(next_guess(10, 1_000, 1_000), 11);
+    }
+}
@@ -0,0 +1,222 @@
+use std::{str::FromStr, time::Instant};
+
+use sui_sdk::{
+    SuiClient,
+    rpc_types::{
+        SuiExecutionStatus, SuiTransactionBlockEffects, SuiTransactionBlockEffectsAPI,
+        SuiTransactionBlockResponse, SuiTransactionBlockResponseOptions,
+    },
+    types::{
+        base_types::SuiAddress,
+        digests::TransactionDigest,
+        gas::GasCostSummary,
+        quorum_driver_types::ExecuteTransactionRequestType,
+        transaction::{ProgrammableTransaction, Transaction, TransactionData, TransactionKind},
+    },
+};
+
+use crate::{
+    consts::SUI_TYPE,
+    error::{Result, SevenKError},
+    library::get_coin_object_ids_by_amount::get_coin_object_ids_by_amount,
+    metrics,
+    signer::Signer,
+    types::{
+        aggregators::{Commission, QuoteResponse},
+        tx::{BuildTxParams, ExecuteSwapParams},
+    },
+    utils::token::check_is_sui,
+};
+
+use super::{build_tx::build_tx, config::ConfigProvider, receipt::SwapReceipt};
+
+// Percent added on top of the dev-inspected gas usage
+const GAS_BUDGET_MARGIN: u64 = 20;
+const MIN_GAS_BUDGET: u64 = 5_000_000;
+
+#[derive(Debug, Clone)]
+pub struct SwapExecution {
+    pub digest: TransactionDigest,
+    pub effects: SuiTransactionBlockEffects,
+    pub response: SuiTransactionBlockResponse,
+    pub sender: SuiAddress,
+    pub quote: QuoteResponse,
+    pub commission: Commission,
+    /// Package that defined the 7k `settle` event the receipt is read from.
+    pub event_package_id: String,
+}
+
+impl SwapExecution {
+    pub fn gas_cost(&self) -> &GasCostSummary {
+        self.effects.gas_cost_summary()
+    }
+
+    /// Compares what the transaction actually moved with what was quoted.
+    pub fn receipt(&self) -> Result<SwapReceipt> {
+        SwapReceipt::from_response(
+            &self.response,
+            &self.quote,
+            &self.commission,
+            self.sender,
+            &self.event_package_id,
+        )
+    }
+}
+
+/// Builds the swap, pays gas with the signer's SUI coins, signs, submits and waits until the
+/// transaction is executed.
+#[tracing::instrument(skip_all, fields(
+    sender = %signer.address(),
+    token_in = %params.common.quote_response.token_in,
+    token_out = %params.common.quote_response.token_out,
+    amount_in = %params.common.quote_response.swap_amount_with_decimal,
+    digest,
+))]
+pub async fn execute_swap(
+    client: &SuiClient,
+    config_manager: &mut dyn ConfigProvider,
+    signer: &dyn Signer,
+    params: ExecuteSwapParams,
+) -> Result<SwapExecution> {
+    let ExecuteSwapParams {
+        mut common,
+        gas_budget,
+        gas_price,
+    } = params;
+
+    let sender = signer.address();
+    if common.account_address.is_empty() {
+        common.account_address = sender.to_string();
+    } else if SuiAddress::from_str(&common.account_address).ok() != Some(sender) {
+        return Err(SevenKError::InvalidParams(format!(
+            "account {} does not match signer {}",
+            common.account_address, sender
+        )));
+    }
+
+    // SUI inputs are split from the gas coin, so the gas payment has to cover them too
+    let sui_in = if check_is_sui(&common.quote_response.token_in) {
+        common
+            .quote_response
+            .swap_amount_with_decimal
+            .parse::<u64>()
+            .unwrap_or_default()
+    } else {
+        0
+    };
+
+    let quote = common.quote_response.clone();
+    let commission = common.commission.clone();
+    let event_package_id = config_manager.contracts().event_package_id.clone();
+
+    let (tx, _) = build_tx(
+        client,
+        config_manager,
+        BuildTxParams {
+            common,
+            dev_inspect: None,
+        },
+    )
+    .await?;
+    let pt = tx.0.finish();
+
+    let gas_price = match gas_price {
+        Some(price) => price,
+        None => client.read_api().get_reference_gas_price().await?,
+    };
+
+    let gas_budget = match gas_budget {
+        Some(budget) => budget,
+        None => estimate_gas_budget(client, sender, &pt).await?,
+    };
+
+    let sui_needed = gas_budget + sui_in;
+    let (_, gas_coins, balance) =
+        get_coin_object_ids_by_amount(client, sender, sui_needed, SUI_TYPE).await?;
+    if balance < sui_needed {
+        return Err(SevenKError::InsufficientBalance {
+            needed: sui_needed,
+            available: balance,
+        });
+    }
+
+    let gas_payment = gas_coins.iter().map(|coin| coin.object_ref()).collect();
+    let tx_data = TransactionData::new_programmable(sender, gas_payment, pt, gas_budget, gas_price);
+    let signature = signer.sign(&tx_data).await?;
+
+    let started = Instant::now();
+    let response = client
+        .quorum_driver_api()
+        .execute_transaction_block(
+            Transaction::from_data(tx_data, vec![signature]),
+            SuiTransactionBlockResponseOptions::full_content(),
+            Some(ExecuteTransactionRequestType::WaitForLocalExecution),
+        )
+        .await?;
+
+    tracing::Span::current().record("digest", response.digest.to_string());
+    tracing::info!(
+        latency_ms = started.elapsed().as_millis() as u64,
+        "swap executed"
+    );
+
+    let effects = response.effects.clone().ok_or_else(|| {
+        SevenKError::Decode(format!("no effects returned for {}", response.digest))
+    })?;
+
+    if let SuiExecutionStatus::Failure { error } = effects.status() {
+        return Err(SevenKError::execution_failed(
+            response.digest.to_string(),
+            error.as_str(),
+        ));
+    }
+
+    let execution = SwapExecution {
+        digest: response.digest,
+        effects,
+        response,
+        sender,
+        quote,
+        commission,
+        event_package_id,
+    };
+    metrics::record_swap_volume(&execution);
+
+    Ok(execution)
+}
+
+async fn estimate_gas_budget(
+    client: &SuiClient,
+    sender: SuiAddress,
+    pt: &ProgrammableTransaction,
+) -> Result<u64> {
+    let started = Instant::now();
+    let dev_inspect = client
+        .read_api()
+        .dev_inspect_transaction_block(
+            sender,
+            TransactionKind::ProgrammableTransaction(pt.clone()),
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+    let SuiTransactionBlockEffects::V1(effects) = &dev_inspect.effects;
+    if let SuiExecutionStatus::Failure { error } = &effects.status {
+        return Err(SevenKError::simulation_failed(error.as_str()));
+    }
+
+    let gas_used = &effects.gas_used;
+    let budget = ((gas_used.computation_cost + gas_used.storage_cost) * (100 + GAS_BUDGET_MARGIN)
+        / 100)
+        .max(MIN_GAS_BUDGET);
+
+    tracing::debug!(
+        gas_budget = budget,
+        latency_ms = started.elapsed().as_millis() as u64,
+        "gas budget estimated"
+    );
+
+    Ok(budget)
+}
@@ -1,7 +1,14 @@
-use crate::types::aggregators::{QuoteResponse, SourceDex};
-use anyhow::Result;
+use std::time::Instant;
+
+use crate::{
+    error::{Result, SevenKError},
+    library::dex_adapter::AdapterRegistry,
+    metrics,
+    settings::ApiSettings,
+    types::aggregators::{QuoteResponse, SourceDex},
+    utils::http::{ApiClient, Endpoint, decode_json},
+};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use sui_sdk::types::base_types::ObjectID;
 
 pub const DEFAULT_SOURCES: &[SourceDex] = &[
@@ -22,7 +29,10 @@ pub const DEFAULT_SOURCES: &[SourceDex] = &[
     SourceDex::Stsui,
 ];
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+/// Quotes `get_buildable_quote` requests before giving up.
+pub const DEFAULT_BUILDABLE_QUOTE_ATTEMPTS: u32 = 3;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct GetQuoteParams {
     pub token_in: String,
     pub token_out: String,
@@ -33,6 +43,24 @@ pub struct GetQuoteParams {
 }
 
 pub async fn get_quote(params: GetQuoteParams) -> Result<QuoteResponse> {
+    request_quote(
+        ApiClient::global(),
+        &ApiSettings::from_env().quote_url,
+        params,
+    )
+    .await
+}
+
+#[tracing::instrument(skip_all, fields(
+    token_in = %params.token_in,
+    token_out = %params.token_out,
+    amount_in = %params.amount_in,
+))]
+pub(crate) async fn request_quote(
+    http: &ApiClient,
+    quote_url: &str,
+    params: GetQuoteParams,
+) -> Result<QuoteResponse> {
     let GetQuoteParams {
         token_in,
         token_out,
@@ -86,31 +114,139 @@ pub async fn get_quote(params: GetQuoteParams) -> Result<QuoteResponse> {
         .collect::<Vec<_>>()
         .join("&");
 
-    let url = format!("https://api.7k.ag/quote?{}", query_string);
+    let url = format!("{}?{}", quote_url, query_string);
+
+    let started = Instant::now();
+    let result = match http.send(Endpoint::Quote, |http| http.get(&url)).await {
+        Ok(response) => decode_json::<QuoteResponse>(response).await,
+        Err(err) => Err(err),
+    };
+    metrics::record_quote(&result, started.elapsed());
+    let quote_response = result?;
+
+    tracing::debug!(
+        return_amount = %quote_response.return_amount_with_decimal,
+        routes = quote_response.routes.as_ref().map_or(0, Vec::len),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "quote received"
+    );
+
+    Ok(quote_response)
+}
+
+/// Quotes through the built-in adapters only, see `request_buildable_quote`.
+pub async fn get_buildable_quote(params: GetQuoteParams) -> Result<QuoteResponse> {
+    request_buildable_quote(
+        ApiClient::global(),
+        &ApiSettings::from_env().quote_url,
+        &AdapterRegistry::default(),
+        params,
+        DEFAULT_BUILDABLE_QUOTE_ATTEMPTS,
+    )
+    .await
+}
 
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
+/// Requests a quote whose every hop has an adapter in `adapters`.
+///
+/// The aggregator may route through pool types this SDK cannot encode. Those sources are dropped
+/// from `sources` and their pools added to `excluded_pools` before quoting again, at most
+/// `max_attempts` quotes in total.
+pub(crate) async fn request_buildable_quote(
+    http: &ApiClient,
+    quote_url: &str,
+    adapters: &AdapterRegistry,
+    mut params: GetQuoteParams,
+    max_attempts: u32,
+) -> Result<QuoteResponse> {
+    let sources = params
+        .sources
+        .take()
+        .unwrap_or_else(|| DEFAULT_SOURCES.to_vec());
+    params.sources = Some(narrow_sources(sources, adapters)?);
 
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to fetch aggregator quote"));
+    let mut unsupported = vec![];
+    for _ in 0..max_attempts.max(1) {
+        let quote = request_quote(http, quote_url, params.clone()).await?;
+
+        unsupported = unsupported_hops(&quote, adapters);
+        if unsupported.is_empty() {
+            return Ok(quote);
+        }
+
+        tracing::debug!(
+            unsupported = unsupported.len(),
+            "quote routes through sources without an adapter, quoting again"
+        );
+        let excluded_pools = params.excluded_pools.get_or_insert_with(Vec::new);
+        for (_, pool_id) in &unsupported {
+            if !excluded_pools.contains(pool_id) {
+                excluded_pools.push(pool_id.clone());
+            }
+        }
     }
 
-    let json_value: Value = response.json().await?;
-    println!("response: {}", serde_json::to_string_pretty(&json_value)?);
+    let mut ids: Vec<&str> = unsupported.iter().map(|(dex, _)| dex.as_str()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    Err(SevenKError::UnsupportedDex(ids.join(",")))
+}
 
-    let quote_response: QuoteResponse = serde_json::from_value(json_value)?;
-    Ok(quote_response)
+/// Keeps the sources that have an adapter.
+fn narrow_sources(sources: Vec<SourceDex>, adapters: &AdapterRegistry) -> Result<Vec<SourceDex>> {
+    let requested = sources.len();
+    let (supported, unsupported): (Vec<_>, Vec<_>) = sources
+        .into_iter()
+        .partition(|source| adapters.contains(source.as_str()));
+
+    if supported.is_empty() && requested > 0 {
+        let ids: Vec<&str> = unsupported.iter().map(SourceDex::as_str).collect();
+        return Err(SevenKError::UnsupportedDex(ids.join(",")));
+    }
+
+    Ok(supported)
+}
+
+/// Pool type and pool ID of every hop without an adapter.
+fn unsupported_hops(quote: &QuoteResponse, adapters: &AdapterRegistry) -> Vec<(SourceDex, String)> {
+    quote
+        .routes
+        .iter()
+        .flatten()
+        .flat_map(|route| &route.hops)
+        .filter(|hop| !adapters.contains(hop.pool.pool_type.as_str()))
+        .map(|hop| (hop.pool.pool_type.clone(), hop.pool_id.clone()))
+        .collect()
 }
 
-fn normalize_struct_tag(tag: &str) -> String {
+pub(crate) fn normalize_struct_tag(tag: &str) -> String {
     // Basic normalization - a more complete implementation would be needed
     tag.to_string()
 }
 
-fn normalize_sui_object_id(id: &str) -> String {
+pub(crate) fn normalize_sui_object_id(id: &str) -> String {
     // Basic normalization - a more complete implementation would be needed
     match ObjectID::from_hex_literal(id) {
         Ok(object_id) => object_id.to_string(),
         Err(_) => id.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_narrow_sources() {
+        let adapters = AdapterRegistry::default();
+        let sources = vec![SourceDex::Cetus, SourceDex::Unknown("magma".to_string())];
+
+        assert_eq!(
+            narrow_sources(sources.clone(), &adapters).unwrap(),
+            vec![SourceDex::Cetus]
+        );
+        assert!(matches!(
+            narrow_sources(sources, &AdapterRegistry::empty()),
+            Err(SevenKError::UnsupportedDex(ids)) if ids == "cetus,magma"
+        ));
+    }
+}
@@ -1,4 +1,8 @@
-use anyhow::Result;
+use crate::{
+    error::Result,
+    settings::ApiSettings,
+    utils::http::{ApiClient, Endpoint, decode_json},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +30,19 @@ pub struct GetSwapHistoryParams {
 }
 
 pub async fn get_swap_history(params: GetSwapHistoryParams) -> Result<TradingHistoryResponse> {
+    request_swap_history(
+        ApiClient::global(),
+        &ApiSettings::from_env().history_url,
+        params,
+    )
+    .await
+}
+
+pub(crate) async fn request_swap_history(
+    http: &ApiClient,
+    history_url: &str,
+    params: GetSwapHistoryParams,
+) -> Result<TradingHistoryResponse> {
     let mut url_params = vec![
         ("addr", params.owner),
         ("offset", params.offset.to_string()),
@@ -42,15 +59,10 @@ pub async fn get_swap_history(params: GetSwapHistoryParams) -> Result<TradingHis
         .collect::<Vec<_>>()
         .join("&");
 
-    let url = format!("https://statistic.7k.ag/trading-history?{}", query_string);
+    let url = format!("{}?{}", history_url, query_string);
 
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?;
-
-    if !response.status().is_success() {
-        return Err(anyhow::anyhow!("Failed to fetch swap history"));
-    }
+    let response = http.send(Endpoint::History, |http| http.get(&url)).await?;
 
-    let history = response.json::<TradingHistoryResponse>().await?;
+    let history: TradingHistoryResponse = decode_json(response).await?;
     Ok(history)
 }
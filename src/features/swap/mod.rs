@@ -1,5 +1,13 @@
+pub mod build_batch_tx;
 pub mod build_tx;
 pub mod config;
+pub mod config_diff;
 pub mod estimate_gas_fee;
+pub mod exact_out;
+pub mod execute_swap;
 pub mod get_quote;
 pub mod get_swap_history;
+pub mod quote_cache;
+pub mod quote_stream;
+pub mod receipt;
+pub mod simulate_swap;
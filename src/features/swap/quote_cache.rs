@@ -0,0 +1,350 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::sync::OnceCell;
+
+use crate::{
+    error::{Result, SevenKError},
+    types::aggregators::QuoteResponse,
+};
+
+use super::get_quote::{GetQuoteParams, normalize_struct_tag, normalize_sui_object_id};
+
+pub const DEFAULT_QUOTE_TTL: Duration = Duration::from_secs(2);
+const DEFAULT_QUOTE_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuoteCacheOptions {
+    /// How long a quote is served after it was received.
+    pub ttl: Duration,
+    /// Amounts are rounded down to a power of ten within this many basis points of them, so
+    /// nearby amounts share an entry. A cached quote may then be for a slightly different
+    /// `amount_in`; only use it for pricing, or re-quote before building. 0 keys exact amounts.
+    pub amount_bucket_bps: u16,
+    /// Entries kept before expired ones are evicted.
+    pub capacity: usize,
+}
+
+impl Default for QuoteCacheOptions {
+    fn default() -> Self {
+        QuoteCacheOptions {
+            ttl: DEFAULT_QUOTE_TTL,
+            amount_bucket_bps: 0,
+            capacity: DEFAULT_QUOTE_CACHE_CAPACITY,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct QuoteCacheStats {
+    /// Served from a fresh entry.
+    pub hits: u64,
+    /// Sent a quote request.
+    pub misses: u64,
+    /// Waited for an identical request already in flight instead of sending one.
+    pub coalesced: u64,
+}
+
+impl QuoteCacheStats {
+    /// Share of lookups that did not send a request.
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses + self.coalesced;
+        if total == 0 {
+            return 0.0;
+        }
+
+        (self.hits + self.coalesced) as f64 / total as f64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct QuoteKey {
+    token_in: String,
+    token_out: String,
+    amount: String,
+    sources: Option<Vec<String>>,
+    target_pools: Option<Vec<String>>,
+    excluded_pools: Option<Vec<String>>,
+}
+
+impl QuoteKey {
+    fn new(params: &GetQuoteParams, amount_bucket_bps: u16) -> Self {
+        let amount = match params.amount_in.parse::<u64>() {
+            Ok(amount) => bucket_amount(amount, amount_bucket_bps).to_string(),
+            Err(_) => params.amount_in.clone(),
+        };
+        let sorted = |mut ids: Vec<String>| {
+            ids.sort_unstable();
+            ids.dedup();
+            ids
+        };
+        let pools = |pools: &Option<Vec<String>>| {
+            pools
+                .as_ref()
+                .map(|pools| sorted(pools.iter().map(|p| normalize_sui_object_id(p)).collect()))
+        };
+
+        QuoteKey {
+            token_in: normalize_struct_tag(&params.token_in),
+            token_out: normalize_struct_tag(&params.token_out),
+            amount,
+            sources: params
+                .sources
+                .as_ref()
+                .map(|sources| sorted(sources.iter().map(|s| s.as_str().to_string()).collect())),
+            target_pools: pools(&params.target_pools),
+            excluded_pools: pools(&params.excluded_pools),
+        }
+    }
+}
+
+type QuoteSlot = Arc<OnceCell<(Instant, QuoteResponse)>>;
+
+/// Short-lived cache of quotes for callers that ask for the same quote many times a second.
+///
+/// Identical requests in flight at the same time share one HTTP request. Failed requests are
+/// not cached. Clones share the entries and the stats.
+#[derive(Debug, Clone)]
+pub struct QuoteCache {
+    inner: Arc<QuoteCacheInner>,
+}
+
+#[derive(Debug)]
+struct QuoteCacheInner {
+    options: QuoteCacheOptions,
+    entries: Mutex<HashMap<QuoteKey, QuoteSlot>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl Default for QuoteCache {
+    fn default() -> Self {
+        Self::new(QuoteCacheOptions::default())
+    }
+}
+
+impl QuoteCache {
+    pub fn new(options: QuoteCacheOptions) -> Self {
+        QuoteCache {
+            inner: Arc::new(QuoteCacheInner {
+                options,
+                entries: Mutex::new(HashMap::new()),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                coalesced: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn options(&self) -> &QuoteCacheOptions {
+        &self.inner.options
+    }
+
+    /// Returns the cached quote for `params` while it is fresh, otherwise awaits `fetch`, or the
+    /// identical request already in flight, and caches its result.
+    pub async fn get_or_fetch(
+        &self,
+        params: &GetQuoteParams,
+        fetch: impl Future<Output = Result<QuoteResponse>>,
+    ) -> Result<QuoteResponse> {
+        let key = QuoteKey::new(params, self.inner.options.amount_bucket_bps);
+        let slot = self.slot(key);
+
+        if let Some((_, quote)) = slot.get() {
+            self.inner.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(quote.clone());
+        }
+
+        let mut fetched = false;
+        let (_, quote) = slot
+            .get_or_try_init(|| async {
+                fetched = true;
+                Ok::<_, SevenKError>((Instant::now(), fetch.await?))
+            })
+            .await?;
+
+        let counter = if fetched {
+            &self.inner.misses
+        } else {
+            &self.inner.coalesced
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        Ok(quote.clone())
+    }
+
+    /// Fresh or in-flight slot for `key`, replacing an expired one.
+    fn slot(&self, key: QuoteKey) -> QuoteSlot {
+        let ttl = self.inner.options.ttl;
+        let is_fresh = |slot: &QuoteSlot| {
+            slot.get()
+                .is_none_or(|(received_at, _)| received_at.elapsed() <= ttl)
+        };
+
+        let mut entries = self
+            .inner
+            .entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        if let Some(slot) = entries.get(&key)
+            && is_fresh(slot)
+        {
+            return slot.clone();
+        }
+
+        if entries.len() >= self.inner.options.capacity {
+            // Slots still in flight keep working for their waiters once dropped from the map
+            entries.retain(|_, slot| slot.initialized() && is_fresh(slot));
+        }
+
+        let slot = QuoteSlot::default();
+        entries.insert(key, slot.clone());
+        slot
+    }
+
+    pub fn stats(&self) -> QuoteCacheStats {
+        QuoteCacheStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            coalesced: self.inner.coalesced.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner
+            .entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drops every entry; requests in flight still complete for their waiters.
+    pub fn clear(&self) {
+        self.inner
+            .entries
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+}
+
+/// Rounds `amount` down to a power of ten no larger than `bucket_bps` of it, so nearby amounts
+/// map to the same value.
+fn bucket_amount(amount: u64, bucket_bps: u16) -> u64 {
+    let width = (amount as u128 * bucket_bps as u128 / 10_000) as u64;
+    if width < 10 {
+        return amount;
+    }
+
+    let step = 10u64.pow(width.ilog10());
+    amount / step * step
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    fn quote(amount_in: &str) -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "swapAmount": amount_in,
+            "returnAmount": "3",
+            "swapAmountWithDecimal": amount_in,
+            "returnAmountWithDecimal": "3000000",
+            "tokenAddresses": [],
+            "tokenIn": "0x2::sui::SUI",
+            "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+            "marketSp": "3",
+            "routes": [],
+            "swaps": [],
+            "warning": ""
+        }))
+        .unwrap()
+    }
+
+    fn params(amount_in: &str) -> GetQuoteParams {
+        GetQuoteParams {
+            token_in: "0x2::sui::SUI".to_string(),
+            token_out:
+                "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
+                    .to_string(),
+            amount_in: amount_in.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bucket_amount() {
+        assert_eq!(bucket_amount(1_234_567, 0), 1_234_567);
+        assert_eq!(bucket_amount(1_234_567, 10), 1_234_000);
+        assert_eq!(bucket_amount(1_234_999, 10), 1_234_000);
+        assert_eq!(bucket_amount(99, 10), 99);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_coalesced() {
+        let cache = QuoteCache::default();
+        let requests = AtomicUsize::new(0);
+        let fetch = || async {
+            requests.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(quote("1000000000"))
+        };
+
+        let params = params("1000000000");
+        let (a, b) = tokio::join!(
+            cache.get_or_fetch(&params, fetch()),
+            cache.get_or_fetch(&params, fetch())
+        );
+        a.unwrap();
+        b.unwrap();
+        cache.get_or_fetch(&params, fetch()).await.unwrap();
+
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            cache.stats(),
+            QuoteCacheStats {
+                hits: 1,
+                misses: 1,
+                coalesced: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_quotes_are_refetched() {
+        let cache = QuoteCache::new(QuoteCacheOptions {
+            ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        let params = params("1000000000");
+
+        cache
+            .get_or_fetch(&params, async { Ok(quote("1000000000")) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        cache
+            .get_or_fetch(&params, async { Ok(quote("1000000000")) })
+            .await
+            .unwrap();
+
+        assert_eq!(cache.stats().misses, 2);
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{collections::BTreeSet, future::Future, time::Duration};
+
+use futures::{Stream, stream};
+use tokio::time::MissedTickBehavior;
+
+use crate::{
+    error::Result, settings::ApiSettings, types::aggregators::QuoteResponse, utils::http::ApiClient,
+};
+
+use super::get_quote::{GetQuoteParams, request_quote};
+
+/// Shortest delay between two quote requests; shorter intervals, including zero, are raised to
+/// it.
+pub const MIN_QUOTE_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteStreamOptions {
+    /// Delay between two quote requests, at least `MIN_QUOTE_INTERVAL`. A slow request delays
+    /// the next one instead of overlapping it.
+    pub interval: Duration,
+    /// Smallest move of `return_amount_with_decimal`, in basis points of the last emitted quote,
+    /// that emits a new quote. 0 emits any change. A different route set always emits.
+    pub min_change_bps: u16,
+}
+
+/// Polls every `interval` and emits any change.
+impl From<Duration> for QuoteStreamOptions {
+    fn from(interval: Duration) -> Self {
+        QuoteStreamOptions {
+            interval,
+            min_change_bps: 0,
+        }
+    }
+}
+
+/// Re-quotes `params` forever, emitting the first quote and then only those that changed, see
+/// `QuoteStreamOptions`. Failed requests are emitted as errors and polling continues.
+pub fn quote_stream(
+    params: GetQuoteParams,
+    options: impl Into<QuoteStreamOptions>,
+) -> impl Stream<Item = Result<QuoteResponse>> {
+    let quote_url = ApiSettings::from_env().quote_url;
+
+    poll_quotes(
+        move || {
+            let quote_url = quote_url.clone();
+            let params = params.clone();
+            async move { request_quote(ApiClient::global(), &quote_url, params).await }
+        },
+        options.into(),
+    )
+}
+
+/// Calls `fetch` on every tick and applies the change detection of `quote_stream`.
+pub(crate) fn poll_quotes<F, Fut>(
+    fetch: F,
+    options: QuoteStreamOptions,
+) -> impl Stream<Item = Result<QuoteResponse>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<QuoteResponse>>,
+{
+    let mut interval = tokio::time::interval(options.interval.max(MIN_QUOTE_INTERVAL));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    stream::unfold(
+        (fetch, interval, None::<QuoteResponse>),
+        move |(mut fetch, mut interval, mut last)| async move {
+            loop {
+                interval.tick().await;
+
+                let quote = match fetch().await {
+                    Ok(quote) => quote,
+                    Err(err) => return Some((Err(err), (fetch, interval, last))),
+                };
+
+                if last
+                    .as_ref()
+                    .is_some_and(|last| !has_changed(last, &quote, options.min_change_bps))
+                {
+                    continue;
+                }
+
+                last = Some(quote.clone());
+                return Some((Ok(quote), (fetch, interval, last)));
+            }
+        },
+    )
+}
+
+fn has_changed(last: &QuoteResponse, quote: &QuoteResponse, min_change_bps: u16) -> bool {
+    if route_set(last) != route_set(quote) {
+        return true;
+    }
+
+    let (Ok(old), Ok(new)) = (
+        last.return_amount_with_decimal.parse::<u64>(),
+        quote.return_amount_with_decimal.parse::<u64>(),
+    ) else {
+        return last.return_amount_with_decimal != quote.return_amount_with_decimal;
+    };
+
+    let change = old.abs_diff(new) as u128;
+    change > 0 && change * 10_000 >= old as u128 * min_change_bps as u128
+}
+
+/// Pools of every route, hop by hop.
+fn route_set(quote: &QuoteResponse) -> BTreeSet<Vec<&str>> {
+    quote
+        .routes
+        .iter()
+        .flatten()
+        .map(|route| route.hops.iter().map(|hop| hop.pool_id.as_str()).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use futures::StreamExt;
+
+    use super::*;
+    use crate::error::SevenKError;
+
+    fn quote(return_amount: &str, pool_id: &str) -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "swapAmount": "1",
+            "returnAmount": "3",
+            "swapAmountWithDecimal": "1000000000",
+            "returnAmountWithDecimal": return_amount,
+            "tokenAddresses": [],
+            "tokenIn": "0x2::sui::SUI",
+            "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+            "marketSp": "3",
+            "routes": [{
+                "hops": [{
+                    "poolId": pool_id,
+                    "tokenInAmount": "1",
+                    "tokenOutAmount": "3",
+                    "tokenIn": "0x2::sui::SUI",
+                    "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+                    "pool": { "allTokens": [], "type": "cetus" }
+                }],
+                "share": 1.0,
+                "tokenIn": "0x2::sui::SUI",
+                "tokenInAmount": "1",
+                "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+                "tokenOutAmount": "3"
+            }],
+            "swaps": [],
+            "warning": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_has_changed() {
+        let last = quote("3000000", "0x1");
+
+        assert!(!has_changed(&last, &quote("3000000", "0x1"), 0));
+        assert!(has_changed(&last, &quote("3000001", "0x1"), 0));
+        assert!(!has_changed(&last, &quote("3002999", "0x1"), 10));
+        assert!(has_changed(&last, &quote("2997000", "0x1"), 10));
+        assert!(has_changed(&last, &quote("3000000", "0x2"), 10));
+    }
+
+    #[tokio::test]
+    async fn test_poll_quotes_emits_changes_and_errors() {
+        let mut responses = VecDeque::from([
+            Ok(quote("3000000", "0x1")),
+            Ok(quote("3000000", "0x1")),
+            Err(SevenKError::Http {
+                status: 503,
+                body: String::new(),
+            }),
+            Ok(quote("3000000", "0x1")),
+            Ok(quote("3100000", "0x1")),
+        ]);
+        let fetch = move || {
+            let response = responses.pop_front().expect("polled past the fixtures");
+            async move { response }
+        };
+
+        let items: Vec<_> = poll_quotes(fetch, Duration::from_millis(1).into())
+            .take(3)
+            .collect()
+            .await;
+
+        assert_eq!(
+            items[0].as_ref().unwrap().return_amount_with_decimal,
+            "3000000"
+        );
+        assert!(matches!(
+            items[1],
+            Err(SevenKError::Http { status: 503, .. })
+        ));
+        assert_eq!(
+            items[2].as_ref().unwrap().return_amount_with_decimal,
+            "3100000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_poll_quotes_zero_interval() {
+        let fetch = || async { Ok(quote("3000000", "0x1")) };
+
+        let items: Vec<_> = poll_quotes(fetch, Duration::ZERO.into())
+            .take(1)
+            .collect()
+            .await;
+
+        assert!(items[0].is_ok());
+    }
+}
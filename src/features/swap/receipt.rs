@@ -0,0 +1,337 @@
+use std::str::FromStr;
+
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use serde::Serialize;
+use serde_json::Value;
+use sui_sdk::{
+    rpc_types::{SuiTransactionBlockEffectsAPI, SuiTransactionBlockResponse},
+    types::{
+        TypeTag,
+        base_types::{ObjectID, SuiAddress},
+        object::Owner,
+    },
+};
+
+use crate::{
+    consts::SUI_TYPE,
+    error::{Result, SevenKError},
+    types::aggregators::{Commission, QuoteResponse},
+};
+
+const SETTLE_MODULE: &str = "settle";
+// `settle::Swap<CoinIn, CoinOut>`, emitted once per settled swap
+const SETTLE_EVENT: &str = "Swap";
+// u64 fields of the settle event
+const AMOUNT_IN_FIELD: &str = "amount_in";
+const AMOUNT_OUT_FIELD: &str = "amount_out";
+const COMMISSION_FIELD: &str = "commission";
+
+/// Fill quality of an executed swap.
+///
+/// Prices are in whole tokens (token out per token in), derived from the quote's
+/// `return_amount` / `swap_amount` so no decimals lookup is needed.
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapReceipt {
+    pub digest: String,
+    pub token_in: String,
+    pub token_out: String,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub quoted_amount_out: u64,
+    pub quoted_price: f64,
+    pub realized_price: f64,
+    /// Relative shortfall of the realized price against the quote; negative when the fill beat
+    /// the quote.
+    pub slippage: f64,
+    /// Commission paid to `Commission::partner`, in raw units of `token_out`.
+    pub commission: u64,
+    /// Net gas cost in MIST (computation + storage - rebate).
+    pub gas_cost: i64,
+}
+
+impl SwapReceipt {
+    /// Reads the amounts from the `settle` event of `event_package_id` for the quoted pair, failing
+    /// with `SevenKError::Decode` when it lacks one of them. Without such an event it falls back
+    /// to the sender's balance changes, which are corrected for gas when SUI is on either side.
+    pub fn from_response(
+        response: &SuiTransactionBlockResponse,
+        quote: &QuoteResponse,
+        commission: &Commission,
+        sender: SuiAddress,
+        event_package_id: &str,
+    ) -> Result<Self> {
+        let effects = response.effects.as_ref().ok_or_else(|| {
+            SevenKError::Decode(format!("no effects returned for {}", response.digest))
+        })?;
+        let gas_cost = effects.gas_cost_summary().net_gas_usage();
+
+        let token_in = parse_type_tag(&quote.token_in)?;
+        let token_out = parse_type_tag(&quote.token_out)?;
+        let sui = parse_type_tag(SUI_TYPE)?;
+
+        let package = AccountAddress::from(ObjectID::from_str(event_package_id)?);
+        let settle_event = settle_event(
+            response
+                .events
+                .iter()
+                .flat_map(|events| &events.data)
+                .map(|event| (&event.type_, &event.parsed_json)),
+            package,
+            &token_in,
+            &token_out,
+        )?;
+
+        let balance_change = |owner: SuiAddress, coin_type: &TypeTag| -> i128 {
+            response
+                .balance_changes
+                .as_ref()
+                .map(|changes| {
+                    changes
+                        .iter()
+                        .filter(|change| {
+                            matches!(change.owner, Owner::AddressOwner(addr) if addr == owner)
+                                && &change.coin_type == coin_type
+                        })
+                        .map(|change| change.amount)
+                        .sum()
+                })
+                .unwrap_or_default()
+        };
+
+        let (amount_in, amount_out, commission_paid) = match settle_event {
+            Some(event) => (
+                event_u64(event, AMOUNT_IN_FIELD)?,
+                event_u64(event, AMOUNT_OUT_FIELD)?,
+                event_u64(event, COMMISSION_FIELD)?,
+            ),
+            None => {
+                let mut spent = -balance_change(sender, &token_in);
+                if token_in == sui {
+                    spent -= gas_cost as i128;
+                }
+
+                let mut received = balance_change(sender, &token_out);
+                if token_out == sui {
+                    received += gas_cost as i128;
+                }
+
+                let commission_paid = match SuiAddress::from_str(&commission.partner) {
+                    Ok(partner) if partner != sender => {
+                        balance_change(partner, &token_out).max(0) as u64
+                    }
+                    _ => 0,
+                };
+
+                (spent.max(0) as u64, received.max(0) as u64, commission_paid)
+            }
+        };
+
+        Ok(SwapReceipt::new(
+            response.digest.to_string(),
+            quote,
+            amount_in,
+            amount_out,
+            commission_paid,
+            gas_cost,
+        ))
+    }
+
+    fn new(
+        digest: String,
+        quote: &QuoteResponse,
+        amount_in: u64,
+        amount_out: u64,
+        commission: u64,
+        gas_cost: i64,
+    ) -> Self {
+        let quoted_amount_in = quote.swap_amount_with_decimal.parse::<f64>().unwrap_or(0.0);
+        let quoted_amount_out = quote.return_amount_with_decimal.parse::<u64>().unwrap_or(0);
+
+        let quoted_price = ratio(
+            quote.return_amount.parse::<f64>().unwrap_or(0.0),
+            quote.swap_amount.parse::<f64>().unwrap_or(0.0),
+        );
+        let raw_quoted_price = ratio(quoted_amount_out as f64, quoted_amount_in);
+        let raw_realized_price = ratio(amount_out as f64, amount_in as f64);
+        let realized_price = quoted_price * ratio(raw_realized_price, raw_quoted_price);
+
+        let slippage = if quoted_price > 0.0 {
+            1.0 - realized_price / quoted_price
+        } else {
+            0.0
+        };
+
+        SwapReceipt {
+            digest,
+            token_in: quote.token_in.clone(),
+            token_out: quote.token_out.clone(),
+            amount_in,
+            amount_out,
+            quoted_amount_out,
+            quoted_price,
+            realized_price,
+            slippage,
+            commission,
+            gas_cost,
+        }
+    }
+}
+
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator == 0.0 {
+        0.0
+    } else {
+        numerator / denominator
+    }
+}
+
+fn parse_type_tag(coin_type: &str) -> Result<TypeTag> {
+    TypeTag::from_str(coin_type)
+        .map_err(|_| SevenKError::InvalidQuote(format!("invalid coin type {}", coin_type)))
+}
+
+/// The `settle::Swap` event of the `token_in`/`token_out` pair. Event types keep the address of
+/// the package that defined them, so `package` is `SevenKContracts::event_package_id`, not the
+/// upgraded package the transaction called. A transaction running several swaps has one event
+/// per swap, so other pairs and other packages are skipped; a pair settled twice cannot be told
+/// apart and is rejected.
+fn settle_event<'a>(
+    events: impl IntoIterator<Item = (&'a StructTag, &'a Value)>,
+    package: AccountAddress,
+    token_in: &TypeTag,
+    token_out: &TypeTag,
+) -> Result<Option<&'a Value>> {
+    let mut matching = events.into_iter().filter(|(type_, _)| {
+        type_.address == package
+            && type_.module.as_str() == SETTLE_MODULE
+            && type_.name.as_str() == SETTLE_EVENT
+            && type_.type_params.len() == 2
+            && &type_.type_params[0] == token_in
+            && &type_.type_params[1] == token_out
+    });
+
+    let event = matching.next().map(|(_, json)| json);
+    if matching.next().is_some() {
+        return Err(SevenKError::Decode(
+            "transaction settles the quoted pair more than once".to_string(),
+        ));
+    }
+
+    Ok(event)
+}
+
+/// Reads a u64 field of a settle event; u64 fields are rendered as strings in event JSON.
+fn event_u64(event: &Value, field: &str) -> Result<u64> {
+    match event.get(field) {
+        Some(Value::String(s)) => s.parse::<u64>().ok(),
+        Some(Value::Number(n)) => n.as_u64(),
+        _ => None,
+    }
+    .ok_or_else(|| SevenKError::Decode(format!("settle event has no u64 field {}", field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_sdk::types::parse_sui_struct_tag;
+
+    use super::*;
+
+    fn quote() -> QuoteResponse {
+        serde_json::from_value(serde_json::json!({
+            "swapAmount": "1",
+            "returnAmount": "3.039844",
+            "swapAmountWithDecimal": "1000000000",
+            "returnAmountWithDecimal": "3039844",
+            "tokenAddresses": [],
+            "tokenIn": "0x2::sui::SUI",
+            "tokenOut": "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+            "marketSp": "0.3288",
+            "routes": [],
+            "swaps": [],
+            "warning": ""
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_receipt_prices() {
+        let receipt = SwapReceipt::new(
+            "digest".to_string(),
+            &quote(),
+            1_000_000_000,
+            3_009_446,
+            0,
+            0,
+        );
+
+        assert_eq!(receipt.quoted_amount_out, 3_039_844);
+        assert!((receipt.quoted_price - 3.039844).abs() < 1e-9);
+        assert!((receipt.realized_price - 3.009446).abs() < 1e-9);
+        assert!((receipt.slippage - 0.01).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_event_u64() {
+        let event = serde_json::json!({ "amount_out": "10", "amount_in": 4, "commission": "x" });
+
+        assert_eq!(event_u64(&event, AMOUNT_OUT_FIELD).unwrap(), 10);
+        assert_eq!(event_u64(&event, AMOUNT_IN_FIELD).unwrap(), 4);
+        assert!(matches!(
+            event_u64(&event, COMMISSION_FIELD),
+            Err(SevenKError::Decode(_))
+        ));
+    }
+
+    #[test]
+    fn test_settle_event_matches_package_and_pair() {
+        let package = "0x17c0b1f7a6ad73f51268f16b8c06c049eecc2f28a270cdd29c06e3d2dea23302";
+        let other = "0x1234";
+        let usdc = "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+        let deep = "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP";
+        let tag = |address: &str, coin_in: &str, coin_out: &str| {
+            parse_sui_struct_tag(&format!("{address}::settle::Swap<{coin_in}, {coin_out}>"))
+                .unwrap()
+        };
+
+        let sui_to_usdc = tag(package, "0x2::sui::SUI", usdc);
+        let usdc_to_deep = tag(package, usdc, deep);
+        let foreign = tag(other, "0x2::sui::SUI", usdc);
+        let other_event = parse_sui_struct_tag(&format!(
+            "{package}::settle::Commission<0x2::sui::SUI, {usdc}>"
+        ))
+        .unwrap();
+        let first = serde_json::json!({ "amount_in": "1000000000", "amount_out": "3000000" });
+        let second = serde_json::json!({ "amount_in": "3000000", "amount_out": "150000000" });
+        let third = serde_json::json!({ "amount_in": "1", "amount_out": "1" });
+        let events = [
+            (&foreign, &third),
+            (&other_event, &third),
+            (&usdc_to_deep, &second),
+            (&sui_to_usdc, &first),
+        ];
+
+        let package = AccountAddress::from(ObjectID::from_str(package).unwrap());
+        let sui = parse_type_tag("0x2::sui::SUI").unwrap();
+        let usdc = parse_type_tag(usdc).unwrap();
+        let event = settle_event(events, package, &sui, &usdc).unwrap().unwrap();
+
+        assert_eq!(event_u64(event, AMOUNT_IN_FIELD).unwrap(), 1_000_000_000);
+        assert_eq!(event_u64(event, AMOUNT_OUT_FIELD).unwrap(), 3_000_000);
+
+        let deep = parse_type_tag(deep).unwrap();
+        assert!(
+            settle_event(events, package, &sui, &deep)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            settle_event(
+                [(&sui_to_usdc, &first), (&sui_to_usdc, &second)],
+                package,
+                &sui,
+                &usdc
+            )
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::{str::FromStr, time::Instant};
+
+use serde::Serialize;
+use sui_sdk::{
+    SuiClient,
+    rpc_types::{DevInspectResults, SuiExecutionStatus, SuiTransactionBlockEffects},
+    types::{base_types::SuiAddress, gas::GasCostSummary, transaction::Argument},
+};
+
+use crate::{
+    error::{Result, SevenKError},
+    metrics,
+    types::tx::{BuildTxParams, ExtendTx, SimulateSwapParams},
+    utils::sui::Ptb,
+};
+
+use super::{build_tx::build_tx_with_min_out, config::ConfigProvider};
+
+/// Outcome of running a swap against current chain state without submitting it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SwapSimulation {
+    /// `return_amount_with_decimal` of the quote.
+    pub expected_amount_out: u64,
+    pub simulated_amount_out: u64,
+    /// Smallest output the slippage bound accepts.
+    pub min_amount_out: u64,
+    /// `simulated_amount_out - expected_amount_out`; negative when the pools moved against
+    /// the quote.
+    pub difference: i128,
+    /// Whether the simulated output satisfies the slippage bound.
+    pub passed: bool,
+    pub gas_used: GasCostSummary,
+}
+
+impl SwapSimulation {
+    /// `difference` relative to the quoted output, in basis points.
+    pub fn difference_bps(&self) -> f64 {
+        if self.expected_amount_out == 0 {
+            return 0.0;
+        }
+
+        self.difference as f64 * 10_000.0 / self.expected_amount_out as f64
+    }
+}
+
+/// Dev-inspects the swap to check the quote against current pool state before signing.
+///
+/// Dev-inspect does not report balance changes, so the output is read with a `coin::value`
+/// call on the merged output coin. The transaction is built without an on-chain minimum so a
+/// stale quote shows up as `passed == false` instead of a `settle` abort.
+#[tracing::instrument(skip_all, fields(
+    token_in = %params.common.quote_response.token_in,
+    token_out = %params.common.quote_response.token_out,
+    amount_in = %params.common.quote_response.swap_amount_with_decimal,
+))]
+pub async fn simulate_swap(
+    client: &SuiClient,
+    config_manager: &mut dyn ConfigProvider,
+    params: SimulateSwapParams,
+) -> Result<SwapSimulation> {
+    let SimulateSwapParams {
+        mut common,
+        min_amount_out,
+    } = params;
+
+    let sender = SuiAddress::from_str(&common.account_address)
+        .map_err(|_| SevenKError::InvalidAddress(common.account_address.clone()))?;
+
+    let quote_response = &common.quote_response;
+    let token_out = quote_response.token_out.clone();
+    let expected_amount_out = quote_response
+        .return_amount_with_decimal
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid return amount {}",
+                quote_response.return_amount_with_decimal
+            ))
+        })?;
+    let min_amount_out = match min_amount_out {
+        Some(amount) => amount,
+        None => common.slippage.min_amount_out(expected_amount_out),
+    };
+
+    // Build as an extension so the output coin is still available to measure
+    let is_extended = common.extend_tx.is_some();
+    if !is_extended {
+        common.extend_tx = Some(ExtendTx {
+            tx: Ptb::new(),
+            coin_in: None,
+        });
+    }
+
+    let (mut tx, coin_out) = build_tx_with_min_out(
+        client,
+        config_manager,
+        BuildTxParams {
+            common,
+            dev_inspect: Some(true),
+        },
+        Some(0),
+    )
+    .await?;
+
+    let coin_out =
+        coin_out.ok_or_else(|| SevenKError::InvalidQuote("quote has no swaps".to_string()))?;
+    let value = tx.coin_value(token_out, coin_out)?;
+
+    if !is_extended {
+        let addy = tx.pure(sender)?;
+        tx.transfer_objects(vec![coin_out], addy)?;
+    }
+
+    let started = Instant::now();
+    let dev_inspect = client
+        .read_api()
+        .dev_inspect_transaction_block(sender, tx.complete(), None, None, None)
+        .await?;
+
+    let SuiTransactionBlockEffects::V1(effects) = &dev_inspect.effects;
+    if let SuiExecutionStatus::Failure { error } = &effects.status {
+        return Err(SevenKError::simulation_failed(error.as_str()));
+    }
+
+    let simulated_amount_out = read_u64_result(&dev_inspect, value)?;
+    tracing::debug!(
+        expected_amount_out,
+        simulated_amount_out,
+        gas_used = effects.gas_used.net_gas_usage(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "swap simulated"
+    );
+
+    let simulation = SwapSimulation {
+        expected_amount_out,
+        simulated_amount_out,
+        min_amount_out,
+        difference: simulated_amount_out as i128 - expected_amount_out as i128,
+        passed: simulated_amount_out >= min_amount_out,
+        gas_used: effects.gas_used.clone(),
+    };
+    metrics::record_simulation(simulation.difference_bps());
+
+    Ok(simulation)
+}
+
+/// Reads the `u64` returned by the command behind `arg`.
+fn read_u64_result(dev_inspect: &DevInspectResults, arg: Argument) -> Result<u64> {
+    let Argument::Result(index) = arg else {
+        return Err(SevenKError::simulation_failed("expected a command result"));
+    };
+
+    let (bytes, _) = dev_inspect
+        .results
+        .as_ref()
+        .and_then(|results| results.get(index as usize))
+        .and_then(|result| result.return_values.first())
+        .ok_or_else(|| {
+            SevenKError::simulation_failed(format!("no return value for command {}", index))
+        })?;
+
+    let bytes: [u8; 8] = bytes.as_slice().try_into().map_err(|_| {
+        SevenKError::simulation_failed(format!("unexpected return value {:?}", bytes))
+    })?;
+
+    Ok(u64::from_le_bytes(bytes))
+}
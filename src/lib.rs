@@ -1,6 +1,10 @@
 pub mod client;
 pub mod consts;
+pub mod error;
 pub mod features;
 pub mod library;
+pub mod metrics;
+pub mod settings;
+pub mod signer;
 pub mod types;
 pub mod utils;
@@ -0,0 +1,373 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use sui_sdk::types::transaction::Argument;
+
+use crate::{
+    error::{Result, SevenKError},
+    types::aggregators::{Config, TxSorSwap},
+};
+
+use super::{
+    protocols::{
+        aftermath, bluefin, bluemove, cetus, deepbook, deepbook_v3, flowx, flowx_v3, kriya,
+        kriya_v3, obric, springsui, stsui, suiswap, turbos,
+    },
+    swap_with_route::Swapper,
+};
+
+/// Builds the hops of one DEX into a transaction.
+pub trait DexAdapter: Send + Sync {
+    /// Pool type the 7k API reports for this DEX, e.g. `cetus`.
+    fn id(&self) -> &str;
+
+    /// Shared objects `swap` takes for this hop. They are resolved in one batch before any hop
+    /// is built.
+    fn shared_objects(&self, swap: &TxSorSwap, config: &Config) -> Result<Vec<String>>;
+
+    /// Swaps `swapper.input_coin_object` and returns the output coin.
+    fn swap(&self, swapper: &mut Swapper<'_>) -> Result<Argument>;
+}
+
+type SharedObjectsFn = fn(&TxSorSwap, &Config) -> Result<Vec<String>>;
+type SwapFn = fn(&mut Swapper<'_>) -> Result<Argument>;
+
+/// Adapter over one of the `protocols` modules.
+#[derive(Clone, Copy)]
+struct BuiltinAdapter {
+    id: &'static str,
+    shared_objects: SharedObjectsFn,
+    swap: SwapFn,
+}
+
+impl DexAdapter for BuiltinAdapter {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn shared_objects(&self, swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+        (self.shared_objects)(swap, config)
+    }
+
+    fn swap(&self, swapper: &mut Swapper<'_>) -> Result<Argument> {
+        (self.swap)(swapper)
+    }
+}
+
+const BUILTIN_ADAPTERS: &[BuiltinAdapter] = &[
+    BuiltinAdapter {
+        id: "aftermath",
+        shared_objects: aftermath::shared_objects,
+        swap: aftermath::swap,
+    },
+    BuiltinAdapter {
+        id: "bluefin",
+        shared_objects: bluefin::shared_objects,
+        swap: bluefin::swap,
+    },
+    BuiltinAdapter {
+        id: "bluemove",
+        shared_objects: bluemove::shared_objects,
+        swap: bluemove::swap,
+    },
+    BuiltinAdapter {
+        id: "cetus",
+        shared_objects: cetus::shared_objects,
+        swap: cetus::swap,
+    },
+    BuiltinAdapter {
+        id: "deepbook",
+        shared_objects: deepbook::shared_objects,
+        swap: deepbook::swap,
+    },
+    BuiltinAdapter {
+        id: "deepbook_v3",
+        shared_objects: deepbook_v3::shared_objects,
+        swap: deepbook_v3::swap,
+    },
+    BuiltinAdapter {
+        id: "flowx",
+        shared_objects: flowx::shared_objects,
+        swap: flowx::swap,
+    },
+    BuiltinAdapter {
+        id: "flowx_v3",
+        shared_objects: flowx_v3::shared_objects,
+        swap: flowx_v3::swap,
+    },
+    BuiltinAdapter {
+        id: "kriya",
+        shared_objects: kriya::shared_objects,
+        swap: kriya::swap,
+    },
+    BuiltinAdapter {
+        id: "kriya_v3",
+        shared_objects: kriya_v3::shared_objects,
+        swap: kriya_v3::swap,
+    },
+    BuiltinAdapter {
+        id: "obric",
+        shared_objects: obric::shared_objects,
+        swap: obric::swap,
+    },
+    BuiltinAdapter {
+        id: "springsui",
+        shared_objects: springsui::shared_objects,
+        swap: springsui::swap,
+    },
+    BuiltinAdapter {
+        id: "stsui",
+        shared_objects: stsui::shared_objects,
+        swap: stsui::swap,
+    },
+    BuiltinAdapter {
+        id: "suiswap",
+        shared_objects: suiswap::shared_objects,
+        swap: suiswap::swap,
+    },
+    BuiltinAdapter {
+        id: "turbos",
+        shared_objects: turbos::shared_objects,
+        swap: turbos::swap,
+    },
+];
+
+/// Adapters by pool type. `default()` holds every built-in protocol; `register` adds private
+/// ones or replaces a built-in.
+#[derive(Clone)]
+pub struct AdapterRegistry {
+    adapters: HashMap<String, Arc<dyn DexAdapter>>,
+}
+
+impl Default for AdapterRegistry {
+    fn default() -> Self {
+        let mut registry = AdapterRegistry::empty();
+        for adapter in BUILTIN_ADAPTERS {
+            registry.register_arc(Arc::new(*adapter));
+        }
+        registry
+    }
+}
+
+impl fmt::Debug for AdapterRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.ids()).finish()
+    }
+}
+
+impl AdapterRegistry {
+    /// Registry without the built-in protocols.
+    pub fn empty() -> Self {
+        AdapterRegistry {
+            adapters: HashMap::new(),
+        }
+    }
+
+    /// Adds `adapter`, returning the one it replaces for the same id.
+    pub fn register(&mut self, adapter: impl DexAdapter + 'static) -> Option<Arc<dyn DexAdapter>> {
+        self.register_arc(Arc::new(adapter))
+    }
+
+    pub fn register_arc(&mut self, adapter: Arc<dyn DexAdapter>) -> Option<Arc<dyn DexAdapter>> {
+        self.adapters.insert(adapter.id().to_string(), adapter)
+    }
+
+    pub fn get(&self, id: &str) -> Result<&Arc<dyn DexAdapter>> {
+        self.adapters
+            .get(id)
+            .ok_or_else(|| SevenKError::UnsupportedDex(id.to_string()))
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.adapters.contains_key(id)
+    }
+
+    /// Fails with `UnsupportedDex` on the first hop whose pool type has no adapter.
+    pub fn ensure_supported(&self, routes: &[Vec<TxSorSwap>]) -> Result<()> {
+        match routes
+            .iter()
+            .flatten()
+            .find(|swap| !self.contains(swap.pool.pool_type.as_str()))
+        {
+            Some(swap) => Err(SevenKError::UnsupportedDex(
+                swap.pool.pool_type.as_str().to_string(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Registered ids, sorted.
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.adapters.keys().map(String::as_str).collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use sui_sdk::types::{
+        base_types::{ObjectID, SequenceNumber},
+        transaction::{CallArg, Command, ObjectArg},
+    };
+
+    use super::*;
+    use crate::{
+        features::swap::{config::default_config, get_quote::DEFAULT_SOURCES},
+        types::aggregators::{Coin, SorPool, SorSwap},
+        utils::{object_ref_cache::ObjectRefCache, sui::Ptb},
+    };
+
+    struct InHouse;
+
+    impl DexAdapter for InHouse {
+        fn id(&self) -> &str {
+            "in_house"
+        }
+
+        fn shared_objects(&self, swap: &TxSorSwap, _config: &Config) -> Result<Vec<String>> {
+            Ok(vec![swap.swap.pool_id.clone()])
+        }
+
+        fn swap(&self, swapper: &mut Swapper<'_>) -> Result<Argument> {
+            Ok(*swapper.input_coin_object)
+        }
+    }
+
+    #[test]
+    fn test_builtin_adapters_cover_default_sources() {
+        let registry = AdapterRegistry::default();
+
+        assert_eq!(registry.ids().len(), DEFAULT_SOURCES.len());
+        for source in DEFAULT_SOURCES {
+            assert!(registry.contains(source.as_str()), "{}", source.as_str());
+        }
+    }
+
+    #[test]
+    fn test_adapters_take_addresses_from_config() {
+        // Package and object IDs must come from `Config` so `/config` upgrades apply without a
+        // release
+        let sources = [
+            ("aftermath", include_str!("protocols/aftermath.rs")),
+            ("bluefin", include_str!("protocols/bluefin.rs")),
+            ("bluemove", include_str!("protocols/bluemove.rs")),
+            ("cetus", include_str!("protocols/cetus.rs")),
+            ("deepbook", include_str!("protocols/deepbook.rs")),
+            ("deepbook_v3", include_str!("protocols/deepbook_v3.rs")),
+            ("flowx", include_str!("protocols/flowx.rs")),
+            ("flowx_v3", include_str!("protocols/flowx_v3.rs")),
+            ("kriya", include_str!("protocols/kriya.rs")),
+            ("kriya_v3", include_str!("protocols/kriya_v3.rs")),
+            ("obric", include_str!("protocols/obric.rs")),
+            ("springsui", include_str!("protocols/springsui.rs")),
+            ("stsui", include_str!("protocols/stsui.rs")),
+            ("suiswap", include_str!("protocols/suiswap.rs")),
+            ("turbos", include_str!("protocols/turbos.rs")),
+        ];
+
+        for (name, source) in sources {
+            assert!(!source.contains("\"0x"), "{} embeds an address", name);
+        }
+    }
+
+    #[test]
+    fn test_register_private_adapter() {
+        let mut registry = AdapterRegistry::default();
+
+        assert!(matches!(
+            registry.get("in_house"),
+            Err(SevenKError::UnsupportedDex(_))
+        ));
+        assert!(registry.register(InHouse).is_none());
+        assert_eq!(registry.get("in_house").unwrap().id(), "in_house");
+    }
+
+    #[test]
+    fn test_kriya_v3_hop_uses_config_addresses() {
+        let usdc = "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC";
+        let pool_id = "0x5af4976b871fa1813362f352fa4cada3883a96191bb7212db1bd5d13685ae305";
+        let package = "0x00000000000000000000000000000000000000000000000000000000000000aa";
+        let version = "0x00000000000000000000000000000000000000000000000000000000000000bb";
+
+        let mut config = default_config();
+        config.kriya_v3.base.package = package.to_string();
+        config.kriya_v3.version = version.to_string();
+
+        let objects = ObjectRefCache::new();
+        for id in [pool_id, version] {
+            objects.insert(ObjectID::from_str(id).unwrap(), SequenceNumber::from_u64(1));
+        }
+
+        let swap = TxSorSwap {
+            swap: SorSwap {
+                pool_id: pool_id.to_string(),
+                asset_in_index: 0,
+                asset_out_index: 1,
+                amount: "1000000000".to_string(),
+                return_amount: "3000000".to_string(),
+                asset_in: "0x2::sui::SUI".to_string(),
+                asset_out: usdc.to_string(),
+                function_name: String::new(),
+                arguments: vec![],
+                extra: Some(HashMap::from([(
+                    "pool_struct_tag".to_string(),
+                    format!("{package}::pool::Pool<0x2::sui::SUI, {usdc}>"),
+                )])),
+            },
+            pool: SorPool {
+                all_tokens: vec![],
+                pool_type: "kriya_v3".into(),
+            },
+            coin_x: Coin {
+                coin_type: "0x2::sui::SUI".to_string(),
+                decimals: 9,
+            },
+            coin_y: Coin {
+                coin_type: usdc.to_string(),
+                decimals: 6,
+            },
+            swap_x_to_y: true,
+        };
+
+        let registry = AdapterRegistry::default();
+        let adapter = registry.get("kriya_v3").unwrap();
+        assert_eq!(
+            adapter.shared_objects(&swap, &config).unwrap(),
+            vec![pool_id.to_string(), version.to_string()]
+        );
+
+        let mut tx = Ptb::new();
+        let input_coin = tx.pure(0u64).unwrap();
+        adapter
+            .swap(&mut Swapper {
+                objects: &objects,
+                swap: &swap,
+                input_coin_object: &input_coin,
+                current_account: "0x1",
+                config: &config,
+                tx: &mut tx,
+            })
+            .unwrap();
+        let pt = tx.0.finish();
+
+        let trade_calls: Vec<_> = pt
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                Command::MoveCall(call) if call.module.as_str() == "trade" => Some(call),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(trade_calls.len(), 2);
+        for call in trade_calls {
+            assert_eq!(call.package, ObjectID::from_str(package).unwrap());
+        }
+        assert!(pt.inputs.iter().any(|input| matches!(
+            input,
+            CallArg::Object(ObjectArg::SharedObject { id, .. })
+                if *id == ObjectID::from_str(version).unwrap()
+        )));
+    }
+}
@@ -1,10 +1,10 @@
-use anyhow::Result;
 use sui_sdk::{
+    SuiClient,
     rpc_types::Coin,
     types::base_types::{ObjectID, SuiAddress},
 };
 
-use crate::client::get_sui_client;
+use crate::error::Result;
 
 fn order_coins(array: &mut [Coin], sort_by: &str) {
     let mut swapped;
@@ -12,7 +12,7 @@ fn order_coins(array: &mut [Coin], sort_by: &str) {
 
     loop {
         swapped = false;
-        for i in 0..array.len() - 1 {
+        for i in 0..array.len().saturating_sub(1) {
             let left_value: u64 = array[i].balance;
             let right_value: u64 = array[i + 1].balance;
             if compare_function(&left_value, &right_value) {
@@ -27,6 +27,7 @@ fn order_coins(array: &mut [Coin], sort_by: &str) {
 }
 
 pub async fn get_coin_object_ids_by_amount(
+    client: &SuiClient,
     address: SuiAddress,
     amount: u64,
     coin_type: &str,
@@ -36,8 +37,7 @@ pub async fn get_coin_object_ids_by_amount(
     let mut next_cursor = None;
 
     while has_next_page {
-        let coins = get_sui_client()
-            .await?
+        let coins = client
             .coin_read_api()
             .get_coins(
                 address,
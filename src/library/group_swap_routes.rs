@@ -1,8 +1,8 @@
-use anyhow::Result;
 // use crate::aggregator::{Coin, QuoteResponse, SorPool, SorRoute, SorSwap, TxSorSwap};
 // use crate::token::denormalize_token_type;
 
 use crate::{
+    error::{Result, SevenKError},
     types::aggregators::{Coin, QuoteResponse, SorPool, SorRoute, SorSwap, TxSorSwap},
     utils::token::denormalize_token_type,
 };
@@ -21,7 +21,7 @@ pub fn group_swap_routes(quote_response: &QuoteResponse) -> Result<Vec<Vec<TxSor
         current_group.push(item.clone());
 
         let next_item = items.get(i + 1);
-        if next_item.is_none() || next_item.as_ref().unwrap().swap.amount.parse::<u64>()? > 0 {
+        if next_item.is_none() || parse_amount(&next_item.as_ref().unwrap().swap.amount)? > 0 {
             grouped_items.push(current_group);
             current_group = vec![];
         }
@@ -34,6 +34,12 @@ pub fn group_swap_routes(quote_response: &QuoteResponse) -> Result<Vec<Vec<TxSor
     Ok(grouped_items)
 }
 
+fn parse_amount(amount: &str) -> Result<u64> {
+    amount
+        .parse::<u64>()
+        .map_err(|_| SevenKError::InvalidQuote(format!("invalid swap amount {}", amount)))
+}
+
 fn map_pool_ids_to_details(routes: &[SorRoute]) -> std::collections::HashMap<String, SorPool> {
     let mut pool_types: std::collections::HashMap<String, SorPool> =
         std::collections::HashMap::new();
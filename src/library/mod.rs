@@ -1,3 +1,4 @@
+pub mod dex_adapter;
 pub mod get_coin_object_ids_by_amount;
 pub mod group_swap_routes;
 pub mod protocols;
@@ -1,31 +1,53 @@
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
 use crate::{
+    error::{Result, SevenKError},
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{sui::ObjectRefFetcher, token::normalize_token_type},
+    types::aggregators::{Config, TxSorSwap},
+    utils::token::normalize_token_type,
 };
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.aftermath;
+    Ok(vec![
+        swap.swap.pool_id.clone(),
+        cfg.pool_registry.clone(),
+        cfg.protocol_fee_vault.clone(),
+        cfg.treasury.clone(),
+        cfg.insurance_fund.clone(),
+        cfg.referral_vault.clone(),
+    ])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let coin_types = swapper.get_type_params()?;
     let lp_coin_type = coin_types.get(0).unwrap();
 
     let pool_id = swapper.swap.swap.pool_id.clone();
-    let return_amount = swapper.swap.swap.return_amount.parse::<u64>()?;
+    let return_amount = swapper
+        .swap
+        .swap
+        .return_amount
+        .parse::<u64>()
+        .map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid return amount {}",
+                swapper.swap.swap.return_amount
+            ))
+        })?;
     let coin_in_type = normalize_token_type(&swapper.swap.swap.asset_in);
     let coin_out_type = normalize_token_type(&swapper.swap.swap.asset_out);
     let input_coin_object = swapper.input_coin_object;
 
     let type_tags = vec![lp_coin_type, coin_in_type, coin_out_type].to_type_tags()?;
     let cfg = &swapper.config.aftermath;
-    let c = &swapper.client;
-
-    let pool_obj = c.shared_obj_mut(&pool_id).await?;
-    let pool_registry = c.shared_obj_mut(&cfg.pool_registry).await?;
-    let protocol_fee_vault = c.shared_obj_mut(&cfg.protocol_fee_vault).await?;
-    let treasury = c.shared_obj_mut(&cfg.treasury).await?;
-    let insurance_fund = c.shared_obj_mut(&cfg.insurance_fund).await?;
-    let referral_vault = c.shared_obj_mut(&cfg.referral_vault).await?;
+
+    let pool_obj = swapper.shared_obj_mut(&pool_id)?;
+    let pool_registry = swapper.shared_obj_mut(&cfg.pool_registry)?;
+    let protocol_fee_vault = swapper.shared_obj_mut(&cfg.protocol_fee_vault)?;
+    let treasury = swapper.shared_obj_mut(&cfg.treasury)?;
+    let insurance_fund = swapper.shared_obj_mut(&cfg.insurance_fund)?;
+    let referral_vault = swapper.shared_obj_mut(&cfg.referral_vault)?;
 
     let args = vec![
         swapper.tx.obj(pool_obj)?,
@@ -1,29 +1,36 @@
 use super::get_adjusted_sqrt_price_limit;
 use crate::{
     destruct,
+    error::{Result, SevenKError},
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::sui::{ArgumentExt, ObjectRefFetcher},
+    types::aggregators::{Config, TxSorSwap},
+    utils::sui::ArgumentExt,
 };
-use anyhow::{Result, anyhow};
 use std::str::FromStr;
 use sui_sdk::types::{base_types::SuiAddress, transaction::Argument};
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.bluefin;
+    Ok(vec![cfg.global_config.clone(), swap.swap.pool_id.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let pool_id = swapper.swap.swap.pool_id.clone();
 
     let coins = &swapper.swap.pool.all_tokens;
     let (coin_x, coin_y) = match coins.as_slice() {
         [coin_x, coin_y] => (coin_x.clone(), coin_y.clone()),
-        _ => return Err(anyhow!("Expected exactly two coins")),
+        _ => {
+            return Err(SevenKError::InvalidQuote(
+                "bluefin pool must have exactly two coins".to_string(),
+            ));
+        }
     };
 
     let swap_x_to_y = swapper.swap.swap_x_to_y;
     let amount_in = swapper.get_input_coin_value()?;
-    let c = &swapper.client;
-
-    let coin_in = c.owned_obj(&swapper.swap.swap.asset_in).await?;
 
-    let coin_in_obj = swapper.tx.obj(coin_in)?;
+    let coin_in_obj = *swapper.input_coin_object;
     let balance_in = swapper
         .tx
         .coin_into_balance(&swapper.swap.swap.asset_in, coin_in_obj)?;
@@ -31,8 +38,8 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let cfg = &swapper.config.bluefin;
 
-    let config = c.shared_obj_mut(&cfg.global_config).await?;
-    let pool_obj = c.shared_obj_mut(&pool_id).await?;
+    let config = swapper.shared_obj_mut(&cfg.global_config)?;
+    let pool_obj = swapper.shared_obj_mut(&pool_id)?;
     let bal_1 = if swap_x_to_y { balance_in } else { balance_out };
     let bal_2 = if swap_x_to_y { balance_out } else { balance_in };
 
@@ -54,13 +61,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let res = swapper
         .tx
-        .move_call(
-            &swapper.config.aftermath.base.package,
-            "pool",
-            "swap",
-            type_tags,
-            args,
-        )?
+        .move_call(&cfg.base.package, "pool", "swap", type_tags, args)?
         .split(2)?;
 
     let (balance_out_x, balance_out_y) = destruct!(2, res);
@@ -74,6 +75,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
         .coin_from_balance(&coin_y.address, balance_out_y)?;
 
     swapper.tx.transfer_or_destroy_zero_coin(
+        swapper.config,
         &swapper.swap.swap.asset_in,
         if swap_x_to_y { coin_out_x } else { coin_out_y },
         Some(SuiAddress::from_str(&swapper.current_account)?),
@@ -1,11 +1,16 @@
 use crate::{
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::sui::ObjectRefFetcher,
+    types::aggregators::{Config, TxSorSwap},
 };
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(_swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.bluemove;
+    Ok(vec![cfg.dex_info.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let type_tags = vec![
         swapper.swap.swap.asset_in.as_str(),
         swapper.swap.swap.asset_out.as_str(),
@@ -14,11 +19,8 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let amount_in = swapper.get_input_coin_value()?;
 
-    let c = &swapper.client;
-
-    let coin_in = c.owned_obj(&swapper.swap.swap.asset_in).await?;
-    let dex_info = c.owned_obj(&swapper.config.bluemove.dex_info).await?;
-    let coin_in_obj = swapper.tx.obj(coin_in)?;
+    let dex_info = swapper.shared_obj_mut(&swapper.config.bluemove.dex_info)?;
+    let coin_in_obj = *swapper.input_coin_object;
 
     let args = vec![
         amount_in,
@@ -28,7 +30,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     ];
 
     let coin_out = swapper.tx.move_call(
-        &swapper.config.aftermath.base.package,
+        &swapper.config.bluemove.base.package,
         "router",
         "swap_exact_input_",
         type_tags,
@@ -1,14 +1,20 @@
 use super::get_default_sqrt_price_limit;
 use crate::{
     destruct,
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::sui::{ArgumentExt, ObjectRefFetcher},
+    types::aggregators::{Config, TxSorSwap},
+    utils::sui::ArgumentExt,
 };
-use anyhow::Result;
 use std::str::FromStr;
 use sui_sdk::types::{base_types::SuiAddress, transaction::Argument};
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.cetus;
+    Ok(vec![cfg.global_config.clone(), swap.swap.pool_id.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let sqrt_price_limit = if swapper.swap.swap_x_to_y {
         get_default_sqrt_price_limit(true)
     } else {
@@ -29,12 +35,11 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let amount_in = swapper.get_input_coin_value()?;
 
-    let c = &swapper.client;
     let cfg = &swapper.config.cetus;
 
     // Get object references
-    let global_config = c.shared_obj_mut(&cfg.global_config).await?;
-    let pool_id = c.shared_obj_mut(&swapper.swap.swap.pool_id).await?;
+    let global_config = swapper.shared_obj_mut(&cfg.global_config)?;
+    let pool_id = swapper.shared_obj_mut(&swapper.swap.swap.pool_id)?;
 
     let args = vec![
         swapper.tx.obj(global_config)?,
@@ -72,6 +77,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     // Transfer or destroy zero coin
     swapper.tx.transfer_or_destroy_zero_coin(
+        swapper.config,
         &swapper.swap.swap.asset_in,
         if swapper.swap.swap_x_to_y {
             receive_a
@@ -1,13 +1,18 @@
 use crate::{
     destruct,
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::sui::{ArgumentExt, ObjectRefFetcher},
+    types::aggregators::{Config, TxSorSwap},
+    utils::sui::ArgumentExt,
 };
-use anyhow::Result;
 use std::str::FromStr;
 use sui_sdk::types::{base_types::SuiAddress, transaction::Argument};
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, _config: &Config) -> Result<Vec<String>> {
+    Ok(vec![swap.swap.pool_id.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let swap_x_to_y = swapper.swap.swap_x_to_y;
     let client_order_id = chrono::Utc::now().timestamp_millis() as u64;
 
@@ -16,12 +21,11 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     let base_asset = type_args.get(0).unwrap();
     let quote_asset = type_args.get(1).unwrap();
 
-    let account_cap = create_account_cap(swapper).await?;
+    let account_cap = create_account_cap(swapper)?;
     let amount_in = swapper.get_input_coin_value()?;
 
-    let c = &swapper.client;
     let cfg = &swapper.config.deepbook;
-    let pool_id = c.shared_obj_mut(&swapper.swap.swap.pool_id).await?;
+    let pool_id = swapper.shared_obj_mut(&swapper.swap.swap.pool_id)?;
 
     let lot_size = swapper
         .swap
@@ -48,13 +52,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
             account_cap.clone(),
             amount_in_round,
             *swapper.input_coin_object,
-            swapper.tx.move_call(
-                "0x2",
-                "coin",
-                "zero",
-                vec![quote_asset.as_str()].to_type_tags()?,
-                vec![],
-            )?,
+            swapper.tx.zero_coin(quote_asset)?,
             swapper.tx.clock()?,
         ];
 
@@ -72,8 +70,9 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
         let (base_coin_ret, quote_coin_ret) = destruct!(2, res);
 
-        delete_account_cap(swapper, account_cap).await?;
+        delete_account_cap(swapper, account_cap)?;
         swapper.tx.transfer_or_destroy_zero_coin(
+            swapper.config,
             &swapper.swap.swap.asset_in,
             base_coin_ret,
             Some(SuiAddress::from_str(swapper.current_account)?),
@@ -102,8 +101,9 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
         let (base_coin_ret, quote_coin_ret) = destruct!(2, res);
 
-        delete_account_cap(swapper, account_cap).await?;
+        delete_account_cap(swapper, account_cap)?;
         swapper.tx.transfer_or_destroy_zero_coin(
+            swapper.config,
             &swapper.swap.swap.asset_in,
             quote_coin_ret,
             Some(SuiAddress::from_str(swapper.current_account)?),
@@ -114,7 +114,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     Ok(result)
 }
 
-async fn create_account_cap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+fn create_account_cap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let cap = swapper.tx.move_call(
         &swapper.config.deepbook.package,
         "clob_v2",
@@ -125,7 +125,7 @@ async fn create_account_cap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     Ok(cap)
 }
 
-async fn delete_account_cap<'a>(swapper: &mut Swapper<'a>, account_cap: Argument) -> Result<()> {
+fn delete_account_cap(swapper: &mut Swapper<'_>, account_cap: Argument) -> Result<()> {
     swapper.tx.move_call(
         &swapper.config.deepbook.package,
         "custodian_v2",
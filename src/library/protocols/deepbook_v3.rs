@@ -1,18 +1,21 @@
 use std::str::FromStr;
 
-use anyhow::Result;
 use sui_sdk::types::{base_types::SuiAddress, transaction::Argument};
 
 use crate::{
     destruct,
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{
-        sui::{ArgumentExt, ObjectRefFetcher},
-        token::normalize_token_type,
-    },
+    types::aggregators::{Config, TxSorSwap},
+    utils::{sui::ArgumentExt, token::normalize_token_type},
 };
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.deepbook_v3;
+    Ok(vec![cfg.sponsor_fund.clone(), swap.swap.pool_id.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let coin_types = swapper.get_type_params()?;
     let coin_x_type = coin_types.get(0).unwrap();
     let swap_x_to_y =
@@ -27,11 +30,10 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
         .collect::<Vec<&str>>()
         .to_type_tags()?;
 
-    let c = &swapper.client;
     let cfg = &swapper.config.deepbook_v3;
 
-    let sponsor_fund = c.shared_obj_mut(&cfg.sponsor_fund).await?;
-    let pool_id = c.shared_obj_mut(swapper.swap.swap.pool_id.as_str()).await?;
+    let sponsor_fund = swapper.shared_obj_mut(&cfg.sponsor_fund)?;
+    let pool_id = swapper.shared_obj_mut(swapper.swap.swap.pool_id.as_str())?;
 
     let args = vec![
         swapper.tx.obj(sponsor_fund)?,
@@ -59,6 +61,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     // Transfer or destroy zero coin
     swapper.tx.transfer_or_destroy_zero_coin(
+        swapper.config,
         &swapper.swap.swap.asset_in,
         coin_in,
         Some(SuiAddress::from_str(&swapper.current_account)?),
@@ -1,18 +1,24 @@
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
 use crate::{
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{sui::ObjectRefFetcher, token::normalize_token_type},
+    types::aggregators::{Config, TxSorSwap},
+    utils::token::normalize_token_type,
 };
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(_swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.flowx;
+    Ok(vec![cfg.container.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let coin_in_type = normalize_token_type(&swapper.swap.swap.asset_in);
     let coin_out_type = normalize_token_type(&swapper.swap.swap.asset_out);
 
     let config = &swapper.config.flowx;
 
-    let container_obj = swapper.client.shared_obj_mut(&config.container).await?;
+    let container_obj = swapper.shared_obj_mut(&config.container)?;
 
     let type_tags = vec![coin_in_type, coin_out_type].to_type_tags()?;
 
@@ -1,12 +1,18 @@
 use super::get_adjusted_sqrt_price_limit;
 use crate::{
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{sui::ObjectRefFetcher, token::normalize_token_type},
+    types::aggregators::{Config, TxSorSwap},
+    utils::token::normalize_token_type,
 };
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(_swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.flowx_v3;
+    Ok(vec![cfg.registry.clone(), cfg.version.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let swap_x_to_y = swapper.swap.swap_x_to_y;
 
     let swap_fee_rate = swapper
@@ -20,8 +26,8 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let config = &swapper.config.flowx_v3;
 
-    let registry_obj = swapper.client.shared_obj_mut(&config.registry).await?;
-    let version_obj = swapper.client.shared_obj_mut(&config.version).await?;
+    let registry_obj = swapper.shared_obj_mut(&config.registry)?;
+    let version_obj = swapper.shared_obj_mut(&config.version)?;
     let clock = swapper.tx.clock()?;
 
     let pool_tokens = &swapper.swap.pool.all_tokens;
@@ -1,18 +1,19 @@
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
 use crate::{
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{
-        sui::{ObjectArgExt, ObjectRefFetcher},
-        token::normalize_token_type,
-    },
+    types::aggregators::{Config, TxSorSwap},
+    utils::token::normalize_token_type,
 };
 
-const PACKAGE_ID: &str = "0xa0eba10b173538c8fecca1dff298e488402cc9ff374f8a12ca7758eebe830b66";
 const MODULE_NAME: &str = "spot_dex";
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, _config: &Config) -> Result<Vec<String>> {
+    Ok(vec![swap.swap.pool_id.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let swap_x_to_y = swapper.swap.swap_x_to_y;
     let coin_in_type = normalize_token_type(&swapper.swap.swap.asset_in);
     let coin_out_type = normalize_token_type(&swapper.swap.swap.asset_out);
@@ -26,7 +27,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     }
     .to_type_tags()?;
 
-    let pool_obj = swapper.client.object_ref(&pool_id).await?.shared_obj(true);
+    let pool_obj = swapper.shared_obj_mut(&pool_id)?;
 
     let args = vec![
         swapper.tx.obj(pool_obj)?,
@@ -36,7 +37,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     ];
 
     let token_out = swapper.tx.move_call(
-        PACKAGE_ID,
+        &swapper.config.kriya.package,
         MODULE_NAME,
         if swap_x_to_y {
             "swap_token_x"
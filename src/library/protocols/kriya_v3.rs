@@ -1,18 +1,20 @@
 use crate::{
     destruct,
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{
-        sui::{ArgumentExt, ObjectRefFetcher},
-        token::normalize_token_type,
-    },
+    types::aggregators::{Config, TxSorSwap},
+    utils::{sui::ArgumentExt, token::normalize_token_type},
 };
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
-const PACKAGE_ID: &str = "0xbd8d4489782042c6fafad4de4bc6a5e0b84a43c6c00647ffd7062d1e2bb7549e";
-const VERSION_ID: &str = "0xf5145a7ac345ca8736cf8c76047d00d6d378f30e81be6f6eb557184d9de93c78";
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    Ok(vec![
+        swap.swap.pool_id.clone(),
+        config.kriya_v3.version.clone(),
+    ])
+}
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let type_tags = swapper
         .get_type_params()?
         .iter()
@@ -20,14 +22,14 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
         .collect::<Vec<_>>()
         .to_type_tags()?;
 
+    let cfg = &swapper.config.kriya_v3;
     let swap_x_to_y = swapper.swap.swap_x_to_y;
     let pool_id = swapper.swap.swap.pool_id.clone();
     let low_limit_price = 4295048017u128;
     let limit_price = 79226673515401279992447579050u128;
 
-    let c = &swapper.client;
-    let pool_obj = c.shared_obj_mut(&pool_id).await?;
-    let version_obj = c.shared_obj_mut(VERSION_ID).await?;
+    let pool_obj = swapper.shared_obj_mut(&pool_id)?;
+    let version_obj = swapper.shared_obj_mut(&cfg.version)?;
     let input_coin_value = swapper.get_input_coin_value()?;
 
     let args = vec![
@@ -49,34 +51,27 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
 
     let res = swapper
         .tx
-        .move_call(PACKAGE_ID, "trade", "flash_swap", type_tags.clone(), args)?
-        .split(2)?;
+        .move_call(
+            &cfg.base.package,
+            "trade",
+            "flash_swap",
+            type_tags.clone(),
+            args,
+        )?
+        .split(3)?;
 
     let (receive_a, receive_b, flash_receipt) = destruct!(3, res);
 
-    swapper.tx.move_call(
-        "0x2",
-        "balance",
-        "destroy_zero",
-        vec![coin_in_type].to_type_tags()?,
-        vec![if swap_x_to_y { receive_a } else { receive_b }],
+    swapper.tx.destroy_zero_balance(
+        coin_in_type,
+        if swap_x_to_y { receive_a } else { receive_b },
     )?;
 
-    let zero_out_coin = swapper.tx.move_call(
-        "0x2",
-        "balance",
-        "zero",
-        vec![coin_out_type].to_type_tags()?,
-        vec![],
-    )?;
+    let zero_out_coin = swapper.tx.zero_balance(coin_out_type)?;
 
-    let input_coin_balance = swapper.tx.move_call(
-        "0x2",
-        "coin",
-        "into_balance",
-        vec![coin_in_type].to_type_tags()?,
-        vec![*swapper.input_coin_object],
-    )?;
+    let input_coin_balance = swapper
+        .tx
+        .coin_into_balance(coin_in_type, *swapper.input_coin_object)?;
 
     let pay_coin_a = if swap_x_to_y {
         input_coin_balance
@@ -97,16 +92,17 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
         swapper.tx.obj(version_obj)?,
     ];
 
-    swapper
-        .tx
-        .move_call(PACKAGE_ID, "trade", "repay_flash_swap", type_tags, args)?;
-
-    let token_out = swapper.tx.move_call(
-        "0x2",
-        "coin",
-        "from_balance",
-        vec![coin_out_type].to_type_tags()?,
-        vec![if swap_x_to_y { receive_b } else { receive_a }],
+    swapper.tx.move_call(
+        &cfg.base.package,
+        "trade",
+        "repay_flash_swap",
+        type_tags,
+        args,
+    )?;
+
+    let token_out = swapper.tx.coin_from_balance(
+        coin_out_type,
+        if swap_x_to_y { receive_b } else { receive_a },
     )?;
 
     Ok(token_out)
@@ -1,51 +1,41 @@
-use anyhow::Result;
 use sui_sdk::types::transaction::Argument;
 
 use crate::{
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{
-        sui::{ObjectArgExt, ObjectRefFetcher},
-        token::normalize_token_type,
-    },
+    types::aggregators::{Config, TxSorSwap},
+    utils::token::normalize_token_type,
 };
 
-const PACKAGE_ID: &str = "0x82e6f4f75441eae97d2d5850f41a09d28c7b64a05b067d37748d471f43aaf3f7";
-const SUI_SYSTEM_STATE_OBJECT_ID: &str = "0x2::system_state::SystemState";
+pub fn shared_objects(swap: &TxSorSwap, _config: &Config) -> Result<Vec<String>> {
+    Ok(vec![swap.swap.pool_id.clone()])
+}
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let pool_tokens = &swapper.swap.pool.all_tokens;
     let is_stake = swapper.swap.swap_x_to_y;
     let coin_y_type = normalize_token_type(&pool_tokens[1].address);
 
     let type_tags = vec![coin_y_type].to_type_tags()?;
 
-    let pool_obj = swapper
-        .client
-        .object_ref(&swapper.swap.swap.pool_id)
-        .await?
-        .shared_obj(true);
-    let system_state_obj = swapper
-        .client
-        .object_ref(SUI_SYSTEM_STATE_OBJECT_ID)
-        .await?
-        .shared_obj(true);
+    let pool_obj = swapper.shared_obj_mut(&swapper.swap.swap.pool_id)?;
 
     let args = if is_stake {
         vec![
             swapper.tx.obj(pool_obj)?,
-            swapper.tx.obj(system_state_obj)?,
+            swapper.tx.sui_system_state()?,
             *swapper.input_coin_object,
         ]
     } else {
         vec![
             swapper.tx.obj(pool_obj)?,
             *swapper.input_coin_object,
-            swapper.tx.obj(system_state_obj)?,
+            swapper.tx.sui_system_state()?,
         ]
     };
 
     let token_out = swapper.tx.move_call(
-        PACKAGE_ID,
+        &swapper.config.springsui.package,
         "liquid_staking",
         if is_stake { "mint" } else { "redeem" },
         type_tags,
@@ -3,13 +3,11 @@
 use super::get_default_sqrt_price_limit;
 use crate::{
     destruct,
+    error::Result,
     library::swap_with_route::{Swapper, ToTypeTags},
-    utils::{
-        sui::{ArgumentExt, ObjectRefFetcher},
-        token::normalize_token_type,
-    },
+    types::aggregators::{Config, TxSorSwap},
+    utils::{sui::ArgumentExt, token::normalize_token_type},
 };
-use anyhow::Result;
 use std::str::FromStr;
 use sui_sdk::types::{TypeTag, base_types::SuiAddress, transaction::Argument};
 
@@ -18,7 +16,12 @@ const MAX_TICK_INDEX: u64 = 443636;
 const MIN_TICK_INDEX: i64 = -443636;
 const ONE_MINUTE: u64 = 60 * 1000;
 
-pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
+pub fn shared_objects(swap: &TxSorSwap, config: &Config) -> Result<Vec<String>> {
+    let cfg = &config.turbos;
+    Ok(vec![swap.swap.pool_id.clone(), cfg.version.clone()])
+}
+
+pub fn swap(swapper: &mut Swapper<'_>) -> Result<Argument> {
     let swap_x_to_y = swapper.swap.swap_x_to_y;
     let type_args_string = swapper.get_type_params()?;
 
@@ -36,10 +39,9 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
         TypeTag::from_str(coin_y_type)
     }?;
 
-    let c = &swapper.client;
     let cfg = &swapper.config.turbos;
-    let pool_id = c.shared_obj_mut(&swapper.swap.swap.pool_id).await?;
-    let version = c.shared_obj_mut(cfg.version.as_str()).await?;
+    let pool_id = swapper.shared_obj_mut(&swapper.swap.swap.pool_id)?;
+    let version = swapper.shared_obj_mut(cfg.version.as_str())?;
 
     let args = vec![
         swapper.tx.obj(pool_id)?,
@@ -75,6 +77,7 @@ pub async fn swap<'a>(swapper: &mut Swapper<'a>) -> Result<Argument> {
     let (token_out, token_in) = destruct!(2, res.split(2)?);
 
     swapper.tx.transfer_or_destroy_zero_coin(
+        swapper.config,
         &swapper.swap.swap.asset_in,
         token_in,
         Some(SuiAddress::from_str(swapper.current_account)?),
@@ -1,24 +1,23 @@
-use std::str::FromStr;
+use std::{collections::HashSet, str::FromStr};
 
-use anyhow::Result;
 use move_core_types::language_storage::StructTag;
-use sui_sdk::{
-    types::{transaction::Argument, TypeTag},
-    SuiClient,
+use sui_sdk::types::{
+    TypeTag,
+    base_types::ObjectID,
+    transaction::{Argument, ObjectArg},
 };
 
 use crate::{
-    types::aggregators::{Config, SourceDex, TxSorSwap},
-    utils::sui::Ptb,
+    error::{Result, SevenKError},
+    types::aggregators::{Config, TxSorSwap},
+    utils::{object_ref_cache::ObjectRefCache, sui::Ptb},
 };
 
-use super::protocols::{
-    aftermath, bluefin, bluemove, cetus, deepbook, deepbook_v3, flowx, flowx_v3, kriya, kriya_v3,
-    obric, springsui, stsui, suiswap, turbos,
-};
+use super::dex_adapter::AdapterRegistry;
 
 pub struct Swapper<'a> {
-    pub client: &'a SuiClient,
+    /// Shared objects of the route, resolved before the PTB is built.
+    pub objects: &'a ObjectRefCache,
     pub swap: &'a TxSorSwap,
     pub input_coin_object: &'a Argument,
     pub current_account: &'a str,
@@ -32,7 +31,10 @@ pub trait ToTypeTags {
 
 impl ToTypeTags for Vec<&str> {
     fn to_type_tags(&self) -> Result<Vec<TypeTag>> {
-        self.iter().map(|s| TypeTag::from_str(s)).collect()
+        Ok(self
+            .iter()
+            .map(|s| TypeTag::from_str(s))
+            .collect::<anyhow::Result<Vec<TypeTag>>>()?)
     }
 }
 
@@ -41,7 +43,8 @@ impl<'a> Swapper<'a> {
         if let Some(extra) = self.swap.swap.extra.as_ref() {
             let pool_struct_tag = extra.get("pool_struct_tag").unwrap(); // todo: remove unwrap and do not err, instead default to "" type
 
-            let tag: StructTag = StructTag::from_str(pool_struct_tag)?;
+            let tag: StructTag = StructTag::from_str(pool_struct_tag)
+                .map_err(|err| SevenKError::InvalidQuote(err.to_string()))?;
 
             let cannonical_types: Vec<String> = tag
                 .type_params
@@ -51,10 +54,21 @@ impl<'a> Swapper<'a> {
 
             Ok(cannonical_types)
         } else {
-            return Err(anyhow::anyhow!("No pool struct tag.."));
+            return Err(SevenKError::InvalidQuote(format!(
+                "no pool struct tag for pool {}",
+                self.swap.swap.pool_id
+            )));
         }
     }
 
+    pub fn shared_obj_mut(&self, object_id: &str) -> Result<ObjectArg> {
+        self.objects.cached_shared_obj(object_id, true)
+    }
+
+    pub fn shared_obj_imut(&self, object_id: &str) -> Result<ObjectArg> {
+        self.objects.cached_shared_obj(object_id, false)
+    }
+
     pub fn get_input_coin_value(&mut self) -> Result<Argument> {
         self.tx.coin_value(
             self.swap.swap.asset_in.clone(),
@@ -62,54 +76,70 @@ impl<'a> Swapper<'a> {
         )
     }
 
-    pub async fn swap(&mut self) -> Result<Argument> {
-        let pool_type = self.swap.pool.pool_type;
-
-        let token_out = match pool_type {
-            SourceDex::Aftermath => aftermath::swap(self).await?,
-            SourceDex::Bluefin => bluefin::swap(self).await?,
-            SourceDex::Bluemove => bluemove::swap(self).await?,
-            SourceDex::Cetus => cetus::swap(self).await?,
-            SourceDex::Deepbook => deepbook::swap(self).await?,
-            SourceDex::DeepbookV3 => deepbook_v3::swap(self).await?,
-            SourceDex::Flowx => flowx::swap(self).await?,
-            SourceDex::FlowxV3 => flowx_v3::swap(self).await?,
-            SourceDex::Kriya => kriya::swap(self).await?,
-            SourceDex::KriyaV3 => kriya_v3::swap(self).await?,
-            SourceDex::Obric => obric::swap(self).await?,
-            SourceDex::Springsui => springsui::swap(self).await?,
-            SourceDex::Stsui => stsui::swap(self).await?,
-            SourceDex::Suiswap => suiswap::swap(self).await?,
-            SourceDex::Turbos => turbos::swap(self).await?,
-        };
+    /// Builds this hop with the adapter registered for its pool type.
+    pub fn swap(&mut self, adapters: &AdapterRegistry) -> Result<Argument> {
+        let adapter = adapters.get(self.swap.pool.pool_type.as_str())?;
+        let _span = tracing::debug_span!(
+            "adapter_swap",
+            dex = adapter.id(),
+            pool_id = %self.swap.swap.pool_id
+        )
+        .entered();
 
-        Ok(token_out)
+        adapter.swap(self)
     }
 }
 
-pub async fn swap_with_route(
-    client: &SuiClient,
-    route: &Vec<TxSorSwap>,
+/// Shared objects every hop of `routes` references (pools, DEX configs, oracles), without
+/// duplicates. Resolve them with `ObjectRefCache::prefetch` before building the routes.
+pub fn route_shared_objects(
+    routes: &[Vec<TxSorSwap>],
+    config: &Config,
+    adapters: &AdapterRegistry,
+) -> Result<Vec<ObjectID>> {
+    let mut seen = HashSet::new();
+    let mut object_ids = vec![];
+
+    for swap in routes.iter().flatten() {
+        let required = adapters
+            .get(swap.pool.pool_type.as_str())?
+            .shared_objects(swap, config)?;
+
+        for object_id in required {
+            let object_id = ObjectID::from_str(&object_id)?;
+            if seen.insert(object_id) {
+                object_ids.push(object_id);
+            }
+        }
+    }
+
+    Ok(object_ids)
+}
+
+/// Appends the hops of `route` to `tx`. Every shared object of the route must already be in
+/// `objects`, see `route_shared_objects`.
+pub fn swap_with_route(
+    route: &[TxSorSwap],
     input_coin_object: Argument,
-    current_account: &String,
+    current_account: &str,
     config: &Config,
+    objects: &ObjectRefCache,
+    adapters: &AdapterRegistry,
     tx: &mut Ptb,
 ) -> Result<Argument> {
     let mut next_coin = input_coin_object;
 
     for swap in route.iter() {
         let mut swapper = Swapper {
-            client,
+            objects,
             swap,
             input_coin_object: &next_coin,
-            current_account: current_account.as_str(),
+            current_account,
             config,
             tx,
         };
 
-        let token_out = swapper.swap().await?;
-
-        next_coin = token_out.clone();
+        next_coin = swapper.swap(adapters)?;
     }
     Ok(next_coin)
 }
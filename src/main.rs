@@ -1,29 +1,447 @@
-use anyhow::Result;
-use features::swap::get_quote::{GetQuoteParams, get_quote};
+use std::{path::PathBuf, str::FromStr};
 
-pub mod client;
-pub mod consts;
-pub mod features;
-pub mod library;
-pub mod types;
-pub mod utils;
+use anyhow::{Context, Result, bail};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use serde_json::Value;
+use sevenk::{
+    client::{Network, SevenKClient},
+    consts::NATIVE_USDC_TOKEN_TYPE,
+    features::swap::{
+        config::default_config, config_diff::ConfigDiff, get_quote::GetQuoteParams,
+        get_swap_history::GetSwapHistoryParams, simulate_swap::SwapSimulation,
+    },
+    signer::{KeystoreSigner, default_keystore_path},
+    types::{
+        aggregators::{Commission, QuoteResponse, SourceDex},
+        slippage::Slippage,
+        tx::{CommonParams, EstimateGasFeeParams, ExecuteSwapParams, SimulateSwapParams},
+    },
+};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore};
+use sui_sdk::types::base_types::SuiAddress;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Parser)]
+#[command(
+    name = "sevenk",
+    version,
+    about = "Quote and swap through the 7k aggregator on Sui"
+)]
+struct Cli {
+    /// Print JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// `mainnet`, `testnet`, `devnet`, `localnet` or a full node URL
+    #[arg(long, global = true, default_value = "mainnet")]
+    network: Network,
+
+    /// Sui keystore holding the sender's key [default: ~/.sui/sui_config/sui.keystore]
+    #[arg(long, global = true)]
+    keystore: Option<PathBuf>,
+
+    /// Sender address; defaults to the only address in the keystore
+    #[arg(long, global = true)]
+    address: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Quote a swap
+    Quote(QuoteArgs),
+    /// Sign and execute a swap
+    Swap {
+        #[command(flatten)]
+        swap: SwapArgs,
+
+        /// Simulate the swap and print what would be executed, without signing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Run a swap against current pool state and compare the output with the quote
+    Simulate(SwapArgs),
+    /// Estimate the gas fee of a swap in USD
+    Gas(SwapArgs),
+    /// USD prices of coin types
+    Price {
+        #[arg(required = true)]
+        coin_types: Vec<String>,
+    },
+    /// Swaps made by the sender
+    History {
+        #[arg(long, default_value_t = 20)]
+        limit: u64,
+
+        #[arg(long, default_value_t = 0)]
+        offset: u64,
+
+        /// Only swaps between these two coin types, e.g. `0x2::sui::SUI-0x...::usdc::USDC`
+        #[arg(long)]
+        pair: Option<String>,
+    },
+    /// Aggregator config
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Coin balances of the sender
+    Coins,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Current config and where it came from
+    Show,
+    /// Fields of the live config that differ from the compiled-in defaults
+    Diff,
+}
+
+#[derive(Args)]
+struct QuoteArgs {
+    /// Coin type sold, e.g. `0x2::sui::SUI`
+    #[arg(long)]
+    from: String,
+
+    /// Coin type bought
+    #[arg(long)]
+    to: String,
+
+    /// Amount sold, in the smallest unit of `--from`
+    #[arg(long)]
+    amount: u64,
+
+    /// Only route through these sources, comma separated
+    #[arg(long, value_delimiter = ',')]
+    sources: Vec<String>,
+}
+
+impl QuoteArgs {
+    fn params(&self) -> GetQuoteParams {
+        GetQuoteParams {
+            token_in: self.from.clone(),
+            token_out: self.to.clone(),
+            amount_in: self.amount.to_string(),
+            sources: (!self.sources.is_empty()).then(|| {
+                self.sources
+                    .iter()
+                    .map(|s| SourceDex::from(s.as_str()))
+                    .collect()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Args)]
+struct SwapArgs {
+    #[command(flatten)]
+    quote: QuoteArgs,
+
+    /// `0.5%`, `50bps` or a number of basis points
+    #[arg(long, default_value = "1%")]
+    slippage: Slippage,
+
+    /// Partner receiving the commission
+    #[arg(long)]
+    partner: Option<String>,
+
+    #[arg(long, default_value_t = 0)]
+    commission_bps: u16,
+}
+
+#[derive(Serialize)]
+struct DryRun<'a> {
+    quote: &'a QuoteResponse,
+    simulation: &'a SwapSimulation,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // let client = SuiClientBuilder::default()
-    //     .build("https://fullnode.mainnet.sui.io:443")
-    //     .await?;
-
-    let quote = get_quote(GetQuoteParams {
-        token_in: "0x2::sui::SUI".to_string(),
-        token_out: "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC"
-            .to_string(),
-        amount_in: "1000000000".to_string(),
-        ..Default::default()
-    })
-    .await?;
+    // Logs go to stderr so `--json` output stays parseable; filter with `RUST_LOG`
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    let client = SevenKClient::builder()
+        .network(cli.network.clone())
+        .build()
+        .await?;
+
+    match &cli.command {
+        Command::Quote(args) => {
+            let quote = client.quote(args.params()).await?;
+            output(cli.json, &quote, print_quote)
+        }
+        Command::Swap { swap, dry_run } => {
+            let common = swap_params(&cli, &client, swap).await?;
 
-    println!("Quote: {:?}", quote);
+            if *dry_run {
+                let quote = common.quote_response.clone();
+                let simulation = client
+                    .simulate_swap(SimulateSwapParams {
+                        common,
+                        min_amount_out: None,
+                    })
+                    .await?;
+
+                let dry_run = DryRun {
+                    quote: &quote,
+                    simulation: &simulation,
+                };
+                return output(cli.json, &dry_run, |dry_run| {
+                    print_quote(dry_run.quote);
+                    print_simulation(dry_run.simulation);
+                });
+            }
+
+            let signer = KeystoreSigner::from_file(keystore_path(&cli)?, sender(&cli)?)?;
+            let execution = client
+                .execute_swap(
+                    &signer,
+                    ExecuteSwapParams {
+                        common,
+                        gas_budget: None,
+                        gas_price: None,
+                    },
+                )
+                .await?;
+            let receipt = execution.receipt()?;
+
+            output(cli.json, &receipt, |receipt| {
+                println!("digest:     {}", receipt.digest);
+                println!("amount in:  {} {}", receipt.amount_in, receipt.token_in);
+                println!("amount out: {} {}", receipt.amount_out, receipt.token_out);
+                println!("quoted out: {}", receipt.quoted_amount_out);
+                println!("slippage:   {:.4}%", receipt.slippage * 100.0);
+                println!("gas:        {} MIST", receipt.gas_cost);
+            })
+        }
+        Command::Simulate(swap) => {
+            let common = swap_params(&cli, &client, swap).await?;
+            let simulation = client
+                .simulate_swap(SimulateSwapParams {
+                    common,
+                    min_amount_out: None,
+                })
+                .await?;
+
+            output(cli.json, &simulation, print_simulation)
+        }
+        Command::Gas(swap) => {
+            let common = swap_params(&cli, &client, swap).await?;
+            let gas_usd = client
+                .estimate_gas(EstimateGasFeeParams {
+                    common,
+                    sui_price: None,
+                })
+                .await?;
+
+            output(cli.json, &gas_usd, |gas_usd| println!("${:.4}", gas_usd))
+        }
+        Command::Price { coin_types } => {
+            let prices = client
+                .prices(coin_types.clone(), NATIVE_USDC_TOKEN_TYPE)
+                .await?;
+
+            output(cli.json, &prices, |prices| {
+                for coin_type in coin_types {
+                    match prices.get(coin_type) {
+                        Some(price) => println!("{}  ${}", coin_type, price),
+                        None => println!("{}  no price", coin_type),
+                    }
+                }
+            })
+        }
+        Command::History {
+            limit,
+            offset,
+            pair,
+        } => {
+            let history = client
+                .history(GetSwapHistoryParams {
+                    owner: sender(&cli)?.to_string(),
+                    offset: *offset,
+                    limit: *limit,
+                    token_pair: pair.clone(),
+                })
+                .await?;
+
+            output(cli.json, &history, |history| {
+                println!("{} swaps", history.count);
+                for item in &history.history {
+                    println!(
+                        "{}  {}  {} {} -> {} {}",
+                        item.timestamp,
+                        item.digest,
+                        item.amount_in,
+                        item.coin_in,
+                        item.amount_out,
+                        item.coin_out
+                    );
+                }
+            })
+        }
+        Command::Config(ConfigCommand::Show) => {
+            let manager = client.config_manager();
+            let config = match manager.refresh().await {
+                Ok(config) => config,
+                Err(_) => manager.get_config(),
+            };
+
+            let shown = serde_json::json!({
+                "source": manager.source(),
+                "config": config,
+            });
+            output(cli.json, &shown, |shown| {
+                println!("source: {}", shown["source"].as_str().unwrap_or_default());
+                print_fields("", &shown["config"]);
+            })
+        }
+        Command::Config(ConfigCommand::Diff) => {
+            let config = client.config_manager().refresh().await?;
+            let diff = ConfigDiff::between(&default_config(), &config)?;
+
+            output(cli.json, &diff, |diff| {
+                if diff.is_empty() {
+                    println!("live config matches the compiled-in defaults");
+                }
+                for change in &diff.changes {
+                    println!(
+                        "{}.{}: {} -> {}",
+                        change.dex, change.field, change.old, change.new
+                    );
+                }
+            })
+        }
+        Command::Coins => {
+            let balances = client
+                .sui_client()
+                .coin_read_api()
+                .get_all_balances(sender(&cli)?)
+                .await?;
+
+            output(cli.json, &balances, |balances| {
+                for balance in balances {
+                    println!(
+                        "{}  {} ({} objects)",
+                        balance.coin_type, balance.total_balance, balance.coin_object_count
+                    );
+                }
+            })
+        }
+    }
+}
+
+fn output<T: Serialize>(json: bool, value: &T, human: impl FnOnce(&T)) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        human(value);
+    }
 
     Ok(())
 }
+
+fn print_quote(quote: &QuoteResponse) {
+    println!(
+        "{} {} -> {} {}",
+        quote.swap_amount, quote.token_in, quote.return_amount, quote.token_out
+    );
+    if let Some(price_impact) = quote.price_impact {
+        println!("price impact: {:.4}%", price_impact * 100.0);
+    }
+
+    for route in quote.routes.iter().flatten() {
+        let hops: Vec<&str> = route
+            .hops
+            .iter()
+            .map(|hop| hop.pool.pool_type.as_str())
+            .collect();
+        let share = route.share.map(|share| share * 100.0).unwrap_or(100.0);
+        println!("  {:>6.2}%  {}", share, hops.join(" > "));
+    }
+}
+
+fn print_simulation(simulation: &SwapSimulation) {
+    println!("expected out:  {}", simulation.expected_amount_out);
+    println!("simulated out: {}", simulation.simulated_amount_out);
+    println!("min received:  {}", simulation.min_amount_out);
+    println!(
+        "difference:    {} ({:.2} bps)",
+        simulation.difference,
+        simulation.difference_bps()
+    );
+    println!("passed:        {}", simulation.passed);
+    println!(
+        "gas:           {} MIST",
+        simulation.gas_used.net_gas_usage()
+    );
+}
+
+/// Prints every leaf of a JSON object as `path = value`.
+fn print_fields(path: &str, value: &Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                print_fields(&path, value);
+            }
+        }
+        Value::Null => {}
+        value => println!("{} = {}", path, value),
+    }
+}
+
+fn keystore_path(cli: &Cli) -> Result<PathBuf> {
+    match &cli.keystore {
+        Some(path) => Ok(path.clone()),
+        None => Ok(default_keystore_path()?),
+    }
+}
+
+fn sender(cli: &Cli) -> Result<SuiAddress> {
+    if let Some(address) = &cli.address {
+        return SuiAddress::from_str(address)
+            .with_context(|| format!("invalid address {}", address));
+    }
+
+    let path = keystore_path(cli)?;
+    let keystore = FileBasedKeystore::new(&path)
+        .with_context(|| format!("failed to open keystore {}", path.display()))?;
+
+    match keystore.addresses().as_slice() {
+        [address] => Ok(*address),
+        [] => bail!("no key in {}", path.display()),
+        _ => bail!(
+            "{} holds several keys, pick one with --address",
+            path.display()
+        ),
+    }
+}
+
+async fn swap_params(cli: &Cli, client: &SevenKClient, args: &SwapArgs) -> Result<CommonParams> {
+    let account_address = sender(cli)?;
+    let quote_response = client.buildable_quote(args.quote.params()).await?;
+
+    Ok(CommonParams {
+        quote_response,
+        account_address: account_address.to_string(),
+        slippage: args.slippage,
+        commission: Commission {
+            partner: args
+                .partner
+                .clone()
+                .unwrap_or_else(|| SuiAddress::ZERO.to_string()),
+            commission_bps: args.commission_bps,
+        },
+        extend_tx: None,
+    })
+}
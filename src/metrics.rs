@@ -0,0 +1,146 @@
+//! Counters and histograms recorded through the `metrics` facade when the `metrics` feature is
+//! enabled. Install a recorder such as `metrics-exporter-prometheus` to export them; without
+//! the feature every function here is a no-op.
+
+use std::time::Duration;
+
+use crate::{
+    error::Result,
+    features::swap::execute_swap::SwapExecution,
+    library::dex_adapter::AdapterRegistry,
+    types::aggregators::{Config, TxSorSwap},
+    utils::object_ref_cache::ObjectRefCache,
+};
+
+/// Quote requests, labelled by `status`.
+pub const QUOTE_REQUESTS: &str = "sevenk_quote_requests_total";
+/// Quote request latency in seconds, labelled by `status`.
+pub const QUOTE_LATENCY: &str = "sevenk_quote_latency_seconds";
+/// `/config` requests, labelled by `status`.
+pub const CONFIG_REFRESHES: &str = "sevenk_config_refreshes_total";
+/// Configs served from the cache file or the compiled-in defaults because `/config` failed,
+/// labelled by `source`.
+pub const CONFIG_FALLBACKS: &str = "sevenk_config_fallbacks_total";
+/// Shared objects fetched over RPC while building swaps, labelled by `dex`.
+pub const OBJECT_FETCHES: &str = "sevenk_object_fetches_total";
+/// Simulated output minus quoted output, in basis points of the quoted output.
+pub const SIMULATION_DEVIATION: &str = "sevenk_simulation_deviation_bps";
+/// Raw input amount of executed swaps, labelled by `token`.
+pub const SWAP_VOLUME_IN: &str = "sevenk_swap_volume_in_total";
+/// Raw output amount of executed swaps, labelled by `token`.
+pub const SWAP_VOLUME_OUT: &str = "sevenk_swap_volume_out_total";
+
+/// Registers units and descriptions with the installed recorder.
+#[cfg(feature = "metrics")]
+pub fn describe() {
+    use ::metrics::{Unit, describe_counter, describe_histogram};
+
+    describe_counter!(QUOTE_REQUESTS, "7k quote requests");
+    describe_histogram!(QUOTE_LATENCY, Unit::Seconds, "7k quote request latency");
+    describe_counter!(CONFIG_REFRESHES, "7k /config requests");
+    describe_counter!(
+        CONFIG_FALLBACKS,
+        "configs served from the cache file or the defaults because /config failed"
+    );
+    describe_counter!(OBJECT_FETCHES, "shared objects fetched over RPC");
+    describe_histogram!(
+        SIMULATION_DEVIATION,
+        "simulated output deviation from the quote in bps"
+    );
+    describe_counter!(SWAP_VOLUME_IN, "raw input amount of executed swaps");
+    describe_counter!(SWAP_VOLUME_OUT, "raw output amount of executed swaps");
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_quote<T>(result: &Result<T>, latency: Duration) {
+    #[cfg(feature = "metrics")]
+    {
+        let status = status(result);
+        ::metrics::counter!(QUOTE_REQUESTS, "status" => status).increment(1);
+        ::metrics::histogram!(QUOTE_LATENCY, "status" => status).record(latency.as_secs_f64());
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_config_refresh<T>(result: &Result<T>) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(CONFIG_REFRESHES, "status" => status(result)).increment(1);
+}
+
+/// `source` is `disk` or `default`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_config_fallback(source: &'static str) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(CONFIG_FALLBACKS, "source" => source).increment(1);
+}
+
+/// Counts the shared objects of `routes` that `objects` does not hold yet, i.e. those the
+/// next `prefetch` fetches over RPC, by the DEX that needs them.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_object_fetches(
+    routes: &[Vec<TxSorSwap>],
+    config: &Config,
+    adapters: &AdapterRegistry,
+    objects: &ObjectRefCache,
+) {
+    #[cfg(feature = "metrics")]
+    {
+        use std::{collections::HashSet, str::FromStr};
+
+        use sui_sdk::types::base_types::ObjectID;
+
+        let mut seen = HashSet::new();
+        for swap in routes.iter().flatten() {
+            let dex = swap.pool.pool_type.as_str();
+            let Ok(adapter) = adapters.get(dex) else {
+                continue;
+            };
+            let Ok(required) = adapter.shared_objects(swap, config) else {
+                continue;
+            };
+
+            let fetched = required
+                .iter()
+                .filter_map(|id| ObjectID::from_str(id).ok())
+                .filter(|id| objects.get(id).is_none() && seen.insert(*id))
+                .count();
+            if fetched > 0 {
+                ::metrics::counter!(OBJECT_FETCHES, "dex" => dex.to_string())
+                    .increment(fetched as u64);
+            }
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_simulation(difference_bps: f64) {
+    #[cfg(feature = "metrics")]
+    ::metrics::histogram!(SIMULATION_DEVIATION).record(difference_bps);
+}
+
+/// Records the amounts actually moved, as read by `SwapExecution::receipt`.
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub(crate) fn record_swap_volume(execution: &SwapExecution) {
+    #[cfg(feature = "metrics")]
+    if let Ok(receipt) = execution.receipt() {
+        ::metrics::counter!(SWAP_VOLUME_IN, "token" => receipt.token_in)
+            .increment(receipt.amount_in);
+        ::metrics::counter!(SWAP_VOLUME_OUT, "token" => receipt.token_out)
+            .increment(receipt.amount_out);
+    }
+}
+
+#[cfg(feature = "metrics")]
+fn status<T>(result: &Result<T>) -> &'static str {
+    use crate::error::SevenKError;
+
+    match result {
+        Ok(_) => "ok",
+        Err(SevenKError::Http { status: 429, .. }) => "rate_limited",
+        Err(SevenKError::Http { .. }) => "http_error",
+        Err(SevenKError::Request(err)) if err.is_timeout() => "timeout",
+        Err(SevenKError::Request(_)) => "transport_error",
+        Err(SevenKError::Decode(_)) => "decode_error",
+        Err(_) => "error",
+    }
+}
@@ -0,0 +1,100 @@
+use crate::consts::{API_URL, PRICES_API_URL, STATISTIC_API_URL};
+
+/// Points every endpoint at one base URL, e.g. a local server replaying recorded fixtures.
+pub const MOCK_SERVER_ENV: &str = "SEVENK_MOCK_SERVER";
+pub const QUOTE_URL_ENV: &str = "SEVENK_QUOTE_URL";
+pub const CONFIG_URL_ENV: &str = "SEVENK_CONFIG_URL";
+pub const PRICES_URL_ENV: &str = "SEVENK_PRICES_URL";
+pub const HISTORY_URL_ENV: &str = "SEVENK_HISTORY_URL";
+
+/// Full URLs of the 7k API endpoints used by the SDK.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiSettings {
+    pub quote_url: String,
+    pub config_url: String,
+    pub prices_url: String,
+    pub history_url: String,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        ApiSettings {
+            quote_url: format!("{}/quote", API_URL),
+            config_url: format!("{}/config", API_URL),
+            prices_url: format!("{}/price", PRICES_API_URL),
+            history_url: format!("{}/trading-history", STATISTIC_API_URL),
+        }
+    }
+}
+
+impl ApiSettings {
+    /// Serves every endpoint from `base_url` under the production paths
+    /// (`/quote`, `/config`, `/price`, `/trading-history`).
+    pub fn mock(base_url: &str) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+
+        ApiSettings {
+            quote_url: format!("{}/quote", base_url),
+            config_url: format!("{}/config", base_url),
+            prices_url: format!("{}/price", base_url),
+            history_url: format!("{}/trading-history", base_url),
+        }
+    }
+
+    /// Production endpoints, overridden by `SEVENK_MOCK_SERVER` and then by the
+    /// per-endpoint `SEVENK_*_URL` variables.
+    pub fn from_env() -> Self {
+        let mut settings = match std::env::var(MOCK_SERVER_ENV) {
+            Ok(base_url) if !base_url.is_empty() => ApiSettings::mock(&base_url),
+            _ => ApiSettings::default(),
+        };
+
+        let overrides = [
+            (QUOTE_URL_ENV, &mut settings.quote_url),
+            (CONFIG_URL_ENV, &mut settings.config_url),
+            (PRICES_URL_ENV, &mut settings.prices_url),
+            (HISTORY_URL_ENV, &mut settings.history_url),
+        ];
+
+        for (key, url) in overrides {
+            if let Ok(value) = std::env::var(key)
+                && !value.is_empty()
+            {
+                *url = value;
+            }
+        }
+
+        settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_settings() {
+        let settings = ApiSettings::mock("http://127.0.0.1:8080/");
+
+        assert_eq!(settings.quote_url, "http://127.0.0.1:8080/quote");
+        assert_eq!(settings.config_url, "http://127.0.0.1:8080/config");
+        assert_eq!(settings.prices_url, "http://127.0.0.1:8080/price");
+        assert_eq!(
+            settings.history_url,
+            "http://127.0.0.1:8080/trading-history"
+        );
+    }
+
+    #[test]
+    fn test_default_settings() {
+        let settings = ApiSettings::default();
+
+        assert_eq!(settings.quote_url, "https://api.7k.ag/quote");
+        assert_eq!(settings.config_url, "https://api.7k.ag/config");
+        assert_eq!(settings.prices_url, "https://prices.7k.ag/price");
+        assert_eq!(
+            settings.history_url,
+            "https://statistic.7k.ag/trading-history"
+        );
+    }
+}
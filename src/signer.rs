@@ -0,0 +1,125 @@
+use std::{future::Future, path::PathBuf, pin::Pin, sync::Arc};
+
+use shared_crypto::intent::{Intent, IntentMessage};
+use sui_keys::keystore::{AccountKeystore, FileBasedKeystore, Keystore};
+use sui_sdk::types::{
+    base_types::SuiAddress,
+    crypto::{Signature, SuiKeyPair},
+    transaction::TransactionData,
+};
+
+use crate::error::{Result, SevenKError};
+
+/// Signs transactions on behalf of a single Sui address.
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync {
+    fn address(&self) -> SuiAddress;
+
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature>;
+}
+
+/// Signs with a key from a Sui keystore file, e.g. `~/.sui/sui_config/sui.keystore`.
+pub struct KeystoreSigner {
+    keystore: Keystore,
+    address: SuiAddress,
+}
+
+impl KeystoreSigner {
+    pub fn new(keystore: Keystore, address: SuiAddress) -> Result<Self> {
+        if !keystore.addresses().contains(&address) {
+            return Err(SevenKError::InvalidAddress(format!(
+                "{} is not in the keystore",
+                address
+            )));
+        }
+
+        Ok(KeystoreSigner { keystore, address })
+    }
+
+    pub fn from_file(path: impl Into<PathBuf>, address: SuiAddress) -> Result<Self> {
+        let keystore = FileBasedKeystore::new(&path.into())?;
+        Self::new(Keystore::File(keystore), address)
+    }
+
+    /// Opens the keystore of the local Sui CLI.
+    pub fn from_default_keystore(address: SuiAddress) -> Result<Self> {
+        Self::from_file(default_keystore_path()?, address)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for KeystoreSigner {
+    fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature> {
+        self.keystore
+            .sign_secure(&self.address, tx_data, Intent::sui_transaction())
+            .map_err(|err| SevenKError::Other(err.into()))
+    }
+}
+
+/// Signs with a keypair held in memory.
+pub struct KeypairSigner {
+    keypair: SuiKeyPair,
+}
+
+impl KeypairSigner {
+    pub fn new(keypair: SuiKeyPair) -> Self {
+        KeypairSigner { keypair }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for KeypairSigner {
+    fn address(&self) -> SuiAddress {
+        SuiAddress::from(&self.keypair.public())
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature> {
+        let intent_msg = IntentMessage::new(Intent::sui_transaction(), tx_data.clone());
+        Ok(Signature::new_secure(&intent_msg, &self.keypair))
+    }
+}
+
+pub type SignFuture = Pin<Box<dyn Future<Output = Result<Signature>> + Send>>;
+
+/// Delegates signing to an external signer such as a KMS or a wallet service.
+pub struct CallbackSigner {
+    address: SuiAddress,
+    callback: Arc<dyn Fn(TransactionData) -> SignFuture + Send + Sync>,
+}
+
+impl CallbackSigner {
+    pub fn new<F>(address: SuiAddress, callback: F) -> Self
+    where
+        F: Fn(TransactionData) -> SignFuture + Send + Sync + 'static,
+    {
+        CallbackSigner {
+            address,
+            callback: Arc::new(callback),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for CallbackSigner {
+    fn address(&self) -> SuiAddress {
+        self.address
+    }
+
+    async fn sign(&self, tx_data: &TransactionData) -> Result<Signature> {
+        (self.callback)(tx_data.clone()).await
+    }
+}
+
+pub fn default_keystore_path() -> Result<PathBuf> {
+    let home = std::env::var("HOME")
+        .map_err(|_| SevenKError::InvalidParams("HOME is not set".to_string()))?;
+
+    Ok(PathBuf::from(home)
+        .join(".sui")
+        .join("sui_config")
+        .join("sui.keystore"))
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SourceDex {
     Suiswap,
     Turbos,
@@ -18,6 +18,9 @@ pub enum SourceDex {
     Springsui,
     Obric,
     Stsui,
+    /// A source this SDK version does not know, kept verbatim so quotes still parse. Swaps
+    /// through it need an adapter registered under the same id.
+    Unknown(String),
 }
 
 impl SourceDex {
@@ -38,6 +41,34 @@ impl SourceDex {
             SourceDex::Springsui => "springsui",
             SourceDex::Obric => "obric",
             SourceDex::Stsui => "stsui",
+            SourceDex::Unknown(s) => s.as_str(),
+        }
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, SourceDex::Unknown(_))
+    }
+}
+
+impl From<&str> for SourceDex {
+    fn from(s: &str) -> Self {
+        match s {
+            "suiswap" => SourceDex::Suiswap,
+            "turbos" => SourceDex::Turbos,
+            "cetus" => SourceDex::Cetus,
+            "bluemove" => SourceDex::Bluemove,
+            "kriya" => SourceDex::Kriya,
+            "kriya_v3" => SourceDex::KriyaV3,
+            "aftermath" => SourceDex::Aftermath,
+            "deepbook" => SourceDex::Deepbook,
+            "deepbook_v3" => SourceDex::DeepbookV3,
+            "flowx" => SourceDex::Flowx,
+            "flowx_v3" => SourceDex::FlowxV3,
+            "bluefin" => SourceDex::Bluefin,
+            "springsui" => SourceDex::Springsui,
+            "obric" => SourceDex::Obric,
+            "stsui" => SourceDex::Stsui,
+            _ => SourceDex::Unknown(s.to_string()),
         }
     }
 }
@@ -57,27 +88,7 @@ impl<'de> Deserialize<'de> for SourceDex {
         D: serde::Deserializer<'de>,
     {
         let s = String::deserialize(deserializer)?;
-        match s.as_str() {
-            "suiswap" => Ok(SourceDex::Suiswap),
-            "turbos" => Ok(SourceDex::Turbos),
-            "cetus" => Ok(SourceDex::Cetus),
-            "bluemove" => Ok(SourceDex::Bluemove),
-            "kriya" => Ok(SourceDex::Kriya),
-            "kriya_v3" => Ok(SourceDex::KriyaV3),
-            "aftermath" => Ok(SourceDex::Aftermath),
-            "deepbook" => Ok(SourceDex::Deepbook),
-            "deepbook_v3" => Ok(SourceDex::DeepbookV3),
-            "flowx" => Ok(SourceDex::Flowx),
-            "flowx_v3" => Ok(SourceDex::FlowxV3),
-            "bluefin" => Ok(SourceDex::Bluefin),
-            "springsui" => Ok(SourceDex::Springsui),
-            "obric" => Ok(SourceDex::Obric),
-            "stsui" => Ok(SourceDex::Stsui),
-            _ => Err(serde::de::Error::custom(format!(
-                "unknown SourceDex value: {}",
-                s
-            ))),
-        }
+        Ok(SourceDex::from(s.as_str()))
     }
 }
 
@@ -724,4 +735,17 @@ mod tests {
         let _deserialized: QuoteResponse =
             serde_json::from_str(&serialized).expect("Failed to deserialize");
     }
+
+    #[test]
+    fn test_unknown_source_dex_round_trip() {
+        let pool: SorPool = serde_json::from_str(r#"{"allTokens": [], "type": "magma"}"#).unwrap();
+
+        assert_eq!(pool.pool_type, SourceDex::Unknown("magma".to_string()));
+        assert!(!pool.pool_type.is_known());
+        assert_eq!(
+            serde_json::to_string(&pool).unwrap(),
+            r#"{"allTokens":[],"type":"magma"}"#
+        );
+        assert_eq!(SourceDex::from("kriya_v3"), SourceDex::KriyaV3);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod aggregators;
+pub mod slippage;
 pub mod token;
 pub mod tx;
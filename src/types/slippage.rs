@@ -0,0 +1,142 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, SevenKError};
+
+pub const MAX_SLIPPAGE_BPS: u16 = 10_000;
+
+/// Slippage tolerance in basis points (1 bps = 0.01%), between 0 and 100%.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Slippage(u16);
+
+impl Slippage {
+    pub fn from_bps(bps: u16) -> Result<Self> {
+        if bps > MAX_SLIPPAGE_BPS {
+            return Err(SevenKError::InvalidParams(format!(
+                "slippage of {} bps is above 100%",
+                bps
+            )));
+        }
+
+        Ok(Slippage(bps))
+    }
+
+    /// `0.5` is 0.5%. Rounded to the nearest basis point.
+    pub fn from_percent(percent: f64) -> Result<Self> {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(SevenKError::InvalidParams(format!(
+                "slippage of {}% is out of range",
+                percent
+            )));
+        }
+
+        Self::from_bps((percent * 100.0).round() as u16)
+    }
+
+    pub fn bps(self) -> u16 {
+        self.0
+    }
+
+    /// Smallest acceptable output for a quoted `amount_out`, rounded down.
+    pub fn min_amount_out(self, amount_out: u64) -> u64 {
+        let remaining = (MAX_SLIPPAGE_BPS - self.0) as u128;
+        (amount_out as u128 * remaining / MAX_SLIPPAGE_BPS as u128) as u64
+    }
+
+    /// Largest acceptable input for a quoted `amount_in`, rounded up.
+    pub fn max_amount_in(self, amount_in: u64) -> u64 {
+        let allowed = (MAX_SLIPPAGE_BPS + self.0) as u128;
+        let max = (amount_in as u128 * allowed).div_ceil(MAX_SLIPPAGE_BPS as u128);
+
+        max.min(u64::MAX as u128) as u64
+    }
+
+    /// `min_amount_out` of a quote's `return_amount_with_decimal`.
+    pub fn min_received(self, return_amount_with_decimal: &str) -> Result<u64> {
+        let amount_out = return_amount_with_decimal.parse::<u64>().map_err(|_| {
+            SevenKError::InvalidQuote(format!(
+                "invalid return amount {}",
+                return_amount_with_decimal
+            ))
+        })?;
+
+        Ok(self.min_amount_out(amount_out))
+    }
+}
+
+impl TryFrom<u16> for Slippage {
+    type Error = SevenKError;
+
+    fn try_from(bps: u16) -> Result<Self> {
+        Slippage::from_bps(bps)
+    }
+}
+
+impl From<Slippage> for u16 {
+    fn from(slippage: Slippage) -> Self {
+        slippage.0
+    }
+}
+
+/// Accepts `0.5%`, `50bps` or a bare number of basis points.
+impl FromStr for Slippage {
+    type Err = SevenKError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let invalid = || SevenKError::InvalidParams(format!("invalid slippage {}", s));
+
+        if let Some(percent) = s.strip_suffix('%') {
+            let percent = percent.trim().parse::<f64>().map_err(|_| invalid())?;
+            return Slippage::from_percent(percent);
+        }
+
+        let bps = s.strip_suffix("bps").unwrap_or(s).trim();
+        Slippage::from_bps(bps.parse::<u16>().map_err(|_| invalid())?)
+    }
+}
+
+impl fmt::Display for Slippage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}%", self.0 / 100, self.0 % 100)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slippage_bounds() {
+        assert!(Slippage::from_bps(10_000).is_ok());
+        assert!(Slippage::from_bps(10_001).is_err());
+        assert!(Slippage::from_percent(-1.0).is_err());
+        assert!(Slippage::from_percent(f64::NAN).is_err());
+        assert!(serde_json::from_str::<Slippage>("20000").is_err());
+    }
+
+    #[test]
+    fn test_slippage_parse() {
+        assert_eq!("0.5%".parse::<Slippage>().unwrap().bps(), 50);
+        assert_eq!("50bps".parse::<Slippage>().unwrap().bps(), 50);
+        assert_eq!("100".parse::<Slippage>().unwrap().bps(), 100);
+        assert!("abc".parse::<Slippage>().is_err());
+        assert_eq!(Slippage::from_bps(5).unwrap().to_string(), "0.05%");
+    }
+
+    #[test]
+    fn test_min_amount_out_is_exact() {
+        let slippage = Slippage::from_bps(100).unwrap();
+
+        // 18-decimal amounts lose their last digits through f64
+        assert_eq!(
+            slippage.min_received("12345678901234567890").unwrap(),
+            12222222112222222211
+        );
+        assert_eq!(slippage.min_amount_out(u64::MAX), 18262276632972456098);
+        assert_eq!(slippage.max_amount_in(1_000_000_001), 1_010_000_002);
+        assert_eq!(Slippage::from_bps(10_000).unwrap().min_amount_out(5), 0);
+    }
+}
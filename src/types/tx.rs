@@ -1,4 +1,7 @@
-use super::aggregators::{Commission, QuoteResponse};
+use super::{
+    aggregators::{Commission, QuoteResponse},
+    slippage::Slippage,
+};
 use crate::utils::sui::Ptb;
 use sui_sdk::types::transaction::Argument;
 
@@ -6,7 +9,7 @@ use sui_sdk::types::transaction::Argument;
 pub struct CommonParams {
     pub quote_response: QuoteResponse,
     pub account_address: String,
-    pub slippage: f64,
+    pub slippage: Slippage,
     pub commission: Commission,
     pub extend_tx: Option<ExtendTx>,
 }
@@ -24,9 +27,48 @@ pub struct BuildTxParams {
     pub dev_inspect: Option<bool>,
 }
 
+// #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildBatchTxParams {
+    /// Swaps of one sender; `extend_tx` must be unset on each of them.
+    pub swaps: Vec<CommonParams>,
+    /// Transaction to append to. When set, the output coins are left to the caller instead of
+    /// being sent to the sender.
+    pub tx: Option<Ptb>,
+    pub dev_inspect: Option<bool>,
+}
+
+// #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildExactOutTxParams {
+    // #[serde(flatten)]
+    pub common: CommonParams,
+    /// Output the transaction must deliver; `settle` aborts below it.
+    pub amount_out: u64,
+    /// Input taken from the sender; whatever the routes do not use is refunded.
+    pub max_amount_in: u64,
+    pub dev_inspect: Option<bool>,
+}
+
 // #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EstimateGasFeeParams {
     // #[serde(flatten)]
     pub common: CommonParams,
     pub sui_price: Option<f64>,
 }
+
+// #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecuteSwapParams {
+    // #[serde(flatten)]
+    pub common: CommonParams,
+    /// Defaults to the dev-inspected gas usage plus a safety margin.
+    pub gas_budget: Option<u64>,
+    /// Defaults to the reference gas price.
+    pub gas_price: Option<u64>,
+}
+
+// #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulateSwapParams {
+    // #[serde(flatten)]
+    pub common: CommonParams,
+    /// Bound checked instead of the slippage-derived minimum, e.g. an exact-out amount.
+    pub min_amount_out: Option<u64>,
+}
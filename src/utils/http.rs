@@ -0,0 +1,413 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{RequestBuilder, Response, header::RETRY_AFTER};
+use serde::de::DeserializeOwned;
+
+use crate::error::{Result, SevenKError};
+
+static GLOBAL: LazyLock<ApiClient> = LazyLock::new(|| ApiClient::from(reqwest::Client::new()));
+
+/// 7k API a request goes to, selecting its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    Quote,
+    Config,
+    Prices,
+    History,
+}
+
+/// Client-side token bucket: `burst` requests at once, refilled at `requests_per_sec`.
+///
+/// A limit that could never let a request through, i.e. a rate that is not a positive number
+/// or a `burst` of 0, disables the limiter instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_sec: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_sec.is_finite() && self.requests_per_sec > 0.0 && self.burst > 0
+    }
+}
+
+/// Timeouts, retries and rate limit applied to every 7k API request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpOptions {
+    pub quote_timeout: Duration,
+    pub config_timeout: Duration,
+    pub prices_timeout: Duration,
+    pub history_timeout: Duration,
+    /// Retries after the first attempt on HTTP 429, 5xx, timeouts and connection errors.
+    pub max_retries: u32,
+    /// Backoff before the first retry, doubled for every further one and jittered.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Longest `Retry-After` waited for; a response asking for more fails right away.
+    pub max_retry_after: Duration,
+    /// Shared by every request of an `ApiClient` and its clones; `None` disables the limiter.
+    pub rate_limit: Option<RateLimit>,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        HttpOptions {
+            quote_timeout: Duration::from_secs(10),
+            config_timeout: Duration::from_secs(10),
+            prices_timeout: Duration::from_secs(5),
+            history_timeout: Duration::from_secs(15),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+            max_retry_after: Duration::from_secs(30),
+            rate_limit: Some(RateLimit {
+                requests_per_sec: 10.0,
+                burst: 10,
+            }),
+        }
+    }
+}
+
+impl HttpOptions {
+    pub fn timeout(&self, endpoint: Endpoint) -> Duration {
+        match endpoint {
+            Endpoint::Quote => self.quote_timeout,
+            Endpoint::Config => self.config_timeout,
+            Endpoint::Prices => self.prices_timeout,
+            Endpoint::History => self.history_timeout,
+        }
+    }
+
+    /// Exponential backoff before retry number `attempt + 1`, half of it random so clients
+    /// retrying together spread out.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.max_backoff);
+
+        backoff / 2 + jitter(backoff / 2)
+    }
+}
+
+#[derive(Debug)]
+pub struct TokenBucket {
+    limit: RateLimit,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    /// Starts full.
+    pub fn new(limit: RateLimit) -> Self {
+        TokenBucket {
+            limit,
+            state: Mutex::new(BucketState {
+                tokens: limit.burst as f64,
+                updated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        if !self.limit.is_enabled() {
+            return Ok(());
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        let elapsed = now
+            .saturating_duration_since(state.updated_at)
+            .as_secs_f64();
+        state.tokens =
+            (state.tokens + elapsed * self.limit.requests_per_sec).min(self.limit.burst as f64);
+        state.updated_at = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            // A tiny rate overflows `Duration`; waiting "forever" is what it asks for
+            Err(
+                Duration::try_from_secs_f64((1.0 - state.tokens) / self.limit.requests_per_sec)
+                    .unwrap_or(Duration::MAX),
+            )
+        }
+    }
+
+    /// Waits until a token is available and takes it.
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// HTTP client for the 7k APIs.
+///
+/// Wraps a pooled `reqwest::Client` with per-endpoint timeouts, retries with backoff that honor
+/// `Retry-After`, and a token bucket. Clones share the pool and the bucket.
+#[derive(Debug, Clone)]
+pub struct ApiClient {
+    http: reqwest::Client,
+    options: Arc<HttpOptions>,
+    limiter: Option<Arc<TokenBucket>>,
+}
+
+impl From<reqwest::Client> for ApiClient {
+    fn from(http: reqwest::Client) -> Self {
+        ApiClient::new(http, HttpOptions::default())
+    }
+}
+
+impl ApiClient {
+    pub fn new(http: reqwest::Client, options: HttpOptions) -> Self {
+        if let Some(limit) = options.rate_limit
+            && !limit.is_enabled()
+        {
+            tracing::warn!(?limit, "rate limit lets no request through, disabling it");
+        }
+
+        ApiClient {
+            http,
+            limiter: options
+                .rate_limit
+                .filter(RateLimit::is_enabled)
+                .map(|limit| Arc::new(TokenBucket::new(limit))),
+            options: Arc::new(options),
+        }
+    }
+
+    /// Client used by the free functions such as `get_quote`, so they share one rate limit.
+    pub fn global() -> &'static ApiClient {
+        &GLOBAL
+    }
+
+    pub fn inner(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    pub fn options(&self) -> &HttpOptions {
+        &self.options
+    }
+
+    /// Sends the request built by `request` until it succeeds, fails with a non-retryable
+    /// error or runs out of retries. `request` is called again for every attempt.
+    pub async fn send(
+        &self,
+        endpoint: Endpoint,
+        request: impl Fn(&reqwest::Client) -> RequestBuilder,
+    ) -> Result<Response> {
+        let timeout = self.options.timeout(endpoint);
+
+        let mut attempt = 0;
+        loop {
+            if let Some(limiter) = &self.limiter {
+                limiter.acquire().await;
+            }
+
+            let started = Instant::now();
+            let (err, retry_after) = match request(&self.http).timeout(timeout).send().await {
+                Ok(response) => {
+                    let retry_after = retry_after(&response);
+                    match ensure_success(response).await {
+                        Ok(response) => {
+                            tracing::trace!(
+                                ?endpoint,
+                                latency_ms = started.elapsed().as_millis() as u64,
+                                "7k API response"
+                            );
+                            return Ok(response);
+                        }
+                        Err(err) => (err, retry_after),
+                    }
+                }
+                Err(err) => (SevenKError::Request(err), None),
+            };
+
+            if attempt >= self.options.max_retries
+                || !err.is_retryable()
+                || retry_after.is_some_and(|wait| wait > self.options.max_retry_after)
+            {
+                return Err(err);
+            }
+
+            let delay = self
+                .options
+                .backoff(attempt)
+                .max(retry_after.unwrap_or_default());
+            tracing::warn!(
+                ?endpoint,
+                attempt = attempt + 1,
+                delay_ms = delay.as_millis() as u64,
+                error = %err,
+                "retrying 7k API request"
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
+fn jitter(max: Duration) -> Duration {
+    // Every `RandomState` gets new keys, which is random enough to spread retries
+    let random = RandomState::new().build_hasher().finish();
+    max.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+/// Turns a non-success response into `SevenKError::Http`, keeping the body for diagnostics.
+pub async fn ensure_success(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    Err(SevenKError::Http {
+        status: status.as_u16(),
+        body: response.text().await.unwrap_or_default(),
+    })
+}
+
+pub async fn decode_json<T: DeserializeOwned>(response: Response) -> Result<T> {
+    response
+        .json()
+        .await
+        .map_err(|err| SevenKError::Decode(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc2822("Wed, 21 Oct 2015 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:27:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let options = HttpOptions::default();
+
+        for attempt in 0..40 {
+            let backoff = options.backoff(attempt);
+            assert!(backoff <= options.max_backoff);
+            assert!(backoff >= options.initial_backoff.min(options.max_backoff) / 2);
+        }
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(RateLimit {
+            requests_per_sec: 2.0,
+            burst: 2,
+        });
+        let start = bucket.state.lock().unwrap().updated_at;
+
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert_eq!(
+            bucket.try_acquire_at(start),
+            Err(Duration::from_millis(500))
+        );
+        assert!(
+            bucket
+                .try_acquire_at(start + Duration::from_millis(500))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_degenerate_rate_limits_do_not_panic() {
+        for limit in [
+            RateLimit {
+                requests_per_sec: 0.0,
+                burst: 10,
+            },
+            RateLimit {
+                requests_per_sec: -1.0,
+                burst: 10,
+            },
+            RateLimit {
+                requests_per_sec: f64::NAN,
+                burst: 10,
+            },
+            RateLimit {
+                requests_per_sec: 10.0,
+                burst: 0,
+            },
+        ] {
+            let client = ApiClient::new(
+                reqwest::Client::new(),
+                HttpOptions {
+                    rate_limit: Some(limit),
+                    ..Default::default()
+                },
+            );
+            assert!(client.limiter.is_none(), "{limit:?}");
+
+            let bucket = TokenBucket::new(limit);
+            for _ in 0..3 {
+                assert_eq!(bucket.try_acquire(), Ok(()), "{limit:?}");
+            }
+        }
+
+        // Positive and finite, so enabled, but the wait for the next token overflows `Duration`
+        let bucket = TokenBucket::new(RateLimit {
+            requests_per_sec: 1e-300,
+            burst: 1,
+        });
+        let start = bucket.state.lock().unwrap().updated_at;
+
+        assert!(bucket.try_acquire_at(start).is_ok());
+        assert_eq!(bucket.try_acquire_at(start), Err(Duration::MAX));
+    }
+}
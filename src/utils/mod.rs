@@ -1,2 +1,4 @@
+pub mod http;
+pub mod object_ref_cache;
 pub mod sui;
 pub mod token;
@@ -0,0 +1,212 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    sync::{Arc, LazyLock, RwLock},
+    time::Instant,
+};
+
+use sui_sdk::{
+    SuiClient,
+    rpc_types::{SuiObjectDataOptions, SuiObjectResponse},
+    types::{
+        SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION, SUI_SYSTEM_STATE_OBJECT_ID,
+        SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+        base_types::{ObjectID, SequenceNumber},
+        object::Owner,
+        transaction::ObjectArg,
+    },
+};
+use tokio::task::JoinSet;
+
+use crate::error::{Result, SevenKError};
+
+// Most objects `multi_get_object_with_options` accepts per call
+const MULTI_GET_LIMIT: usize = 50;
+
+static GLOBAL: LazyLock<ObjectRefCache> = LazyLock::new(ObjectRefCache::new);
+
+/// Initial shared versions of shared objects.
+///
+/// `initial_shared_version` is fixed when an object becomes shared, so entries are never
+/// evicted. Clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct ObjectRefCache {
+    shared_versions: Arc<RwLock<HashMap<ObjectID, SequenceNumber>>>,
+}
+
+impl Default for ObjectRefCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ObjectRefCache {
+    /// Starts with the system objects whose versions are known.
+    pub fn new() -> Self {
+        let shared_versions = HashMap::from([
+            (SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION),
+            (
+                SUI_SYSTEM_STATE_OBJECT_ID,
+                SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+            ),
+        ]);
+
+        ObjectRefCache {
+            shared_versions: Arc::new(RwLock::new(shared_versions)),
+        }
+    }
+
+    /// Cache used by the `ObjectRefFetcher` implementation of `SuiClient`.
+    pub fn global() -> &'static ObjectRefCache {
+        &GLOBAL
+    }
+
+    pub fn get(&self, object_id: &ObjectID) -> Option<SequenceNumber> {
+        self.shared_versions
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(object_id)
+            .copied()
+    }
+
+    pub fn insert(&self, object_id: ObjectID, initial_shared_version: SequenceNumber) {
+        self.shared_versions
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(object_id, initial_shared_version);
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared_versions
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Resolves every uncached ID, issuing the `multi_get_object_with_options` calls for all
+    /// chunks concurrently. Fails if any of them is missing or not shared.
+    #[tracing::instrument(skip_all, fields(requested = object_ids.len(), missing))]
+    pub async fn prefetch(&self, client: &SuiClient, object_ids: &[ObjectID]) -> Result<()> {
+        let mut seen = HashSet::new();
+        let missing: Vec<ObjectID> = object_ids
+            .iter()
+            .filter(|id| self.get(id).is_none() && seen.insert(**id))
+            .copied()
+            .collect();
+
+        tracing::Span::current().record("missing", missing.len());
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let started = Instant::now();
+
+        let mut tasks = JoinSet::new();
+        for chunk in missing.chunks(MULTI_GET_LIMIT) {
+            let client = client.clone();
+            let chunk = chunk.to_vec();
+
+            tasks.spawn(async move {
+                let responses = client
+                    .read_api()
+                    .multi_get_object_with_options(
+                        chunk.clone(),
+                        SuiObjectDataOptions::new().with_owner(),
+                    )
+                    .await?;
+
+                Ok::<_, SevenKError>((chunk, responses))
+            });
+        }
+
+        while let Some(joined) = tasks.join_next().await {
+            let (chunk, responses) = joined.map_err(|err| SevenKError::Other(err.into()))??;
+
+            for (object_id, response) in chunk.iter().zip(responses) {
+                self.insert(*object_id, shared_version(object_id, response)?);
+            }
+        }
+
+        tracing::debug!(
+            latency_ms = started.elapsed().as_millis() as u64,
+            "shared objects fetched"
+        );
+
+        Ok(())
+    }
+
+    pub async fn shared_obj(
+        &self,
+        client: &SuiClient,
+        object_id: ObjectID,
+        mutable: bool,
+    ) -> Result<ObjectArg> {
+        if self.get(&object_id).is_none() {
+            self.prefetch(client, &[object_id]).await?;
+        }
+
+        self.cached_shared_obj(&object_id.to_string(), mutable)
+    }
+
+    /// Shared object argument for an ID already resolved by `prefetch`, without any RPC call.
+    pub fn cached_shared_obj(&self, object_id: &str, mutable: bool) -> Result<ObjectArg> {
+        let id = ObjectID::from_str(object_id)?;
+        let initial_shared_version = self.get(&id).ok_or_else(|| SevenKError::ObjectFetch {
+            object_id: object_id.to_string(),
+            reason: "not prefetched".to_string(),
+        })?;
+
+        Ok(ObjectArg::SharedObject {
+            id,
+            initial_shared_version,
+            mutable,
+        })
+    }
+}
+
+fn shared_version(object_id: &ObjectID, response: SuiObjectResponse) -> Result<SequenceNumber> {
+    let fetch_error = |reason: String| SevenKError::ObjectFetch {
+        object_id: object_id.to_string(),
+        reason,
+    };
+
+    if let Some(error) = response.error {
+        return Err(fetch_error(error.to_string()));
+    }
+
+    match response.data.and_then(|data| data.owner) {
+        Some(Owner::Shared {
+            initial_shared_version,
+        }) => Ok(initial_shared_version),
+        Some(owner) => Err(fetch_error(format!(
+            "not a shared object, owner is {}",
+            owner
+        ))),
+        None => Err(fetch_error("no owner returned".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_objects_are_cached() {
+        let cache = ObjectRefCache::new();
+
+        assert_eq!(
+            cache.get(&SUI_CLOCK_OBJECT_ID),
+            Some(SUI_CLOCK_OBJECT_SHARED_VERSION)
+        );
+
+        let clone = cache.clone();
+        let pool = ObjectID::from_hex_literal("0x1234").unwrap();
+        clone.insert(pool, SequenceNumber::from_u64(42));
+
+        assert_eq!(cache.get(&pool), Some(SequenceNumber::from_u64(42)));
+        assert_eq!(cache.len(), 3);
+    }
+}
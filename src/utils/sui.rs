@@ -1,14 +1,19 @@
 use crate::consts::SUI_TYPE;
+use crate::error::{Result, SevenKError};
 use crate::library::get_coin_object_ids_by_amount::get_coin_object_ids_by_amount;
-use anyhow::{Result, anyhow};
+use crate::types::aggregators::Config;
+use crate::utils::object_ref_cache::ObjectRefCache;
+use anyhow::anyhow;
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
 use sui_sdk::rpc_types::SuiObjectDataOptions;
 use sui_sdk::types::base_types::ObjectRef;
-use sui_sdk::types::object::OBJECT_START_VERSION;
 use sui_sdk::types::transaction::TransactionKind;
 use sui_sdk::types::type_input::TypeInput;
-use sui_sdk::types::{SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION};
+use sui_sdk::types::{
+    SUI_CLOCK_OBJECT_ID, SUI_CLOCK_OBJECT_SHARED_VERSION, SUI_SYSTEM_STATE_OBJECT_ID,
+    SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+};
 use sui_sdk::{
     SuiClient,
     rpc_types::{Coin, SuiObjectResponse, SuiObjectResponseQuery},
@@ -93,7 +98,7 @@ impl Ptb {
         let split_amounts = split_amounts
             .iter()
             .map(|item| self.pure(item))
-            .collect::<Result<Vec<Argument>>>()?;
+            .collect::<anyhow::Result<Vec<Argument>>>()?;
 
         Ok(self.command(Command::SplitCoins(coin_object, split_amounts)))
     }
@@ -171,7 +176,7 @@ impl Ptb {
                 let other_coins = coins_x[1..]
                     .iter()
                     .map(|c| self.obj(ObjectArg::ImmOrOwnedObject(c.object_ref())))
-                    .collect::<Result<Vec<_>>>()?;
+                    .collect::<anyhow::Result<Vec<_>>>()?;
 
                 self.command(Command::MergeCoins(first_coin, other_coins));
             }
@@ -204,6 +209,18 @@ impl Ptb {
         )))
     }
 
+    pub fn destroy_zero_balance(&mut self, coin_type: &str, balance: Argument) -> Result<()> {
+        self.command(Command::move_call(
+            ObjectID::from_str("0x2")?,
+            Identifier::from_str("balance")?,
+            Identifier::from_str("destroy_zero")?,
+            vec![TypeTag::from_str(coin_type)?],
+            vec![balance],
+        ));
+
+        Ok(())
+    }
+
     pub fn coin_into_balance(
         &mut self,
         coin_type: &str,
@@ -228,8 +245,10 @@ impl Ptb {
         )))
     }
 
+    /// Sends `coin` to `to`, or destroys it when empty, through the Cetus `utils` module.
     pub fn transfer_or_destroy_zero_coin(
         &mut self,
+        config: &Config,
         coin_type: &str,
         coin: Argument,
         to: Option<SuiAddress>,
@@ -242,13 +261,11 @@ impl Ptb {
 
         let mut args = vec![coin];
         if let Some(addr) = to {
-            args.push(self.pure(addr.to_string())?);
+            args.push(self.pure(addr)?);
         }
 
         self.command(Command::move_call(
-            ObjectID::from_str(
-                "0x6f5e582ede61fe5395b50c4a449ec11479a54d7ff8e0158247adfda60d98970b",
-            )?,
+            ObjectID::from_str(&config.cetus.base.package)?,
             Identifier::from_str("utils")?,
             Identifier::from_str(target)?,
             vec![TypeTag::from_str(coin_type)?],
@@ -259,14 +276,14 @@ impl Ptb {
     }
 
     pub fn coin_to_arg(&mut self, coin: &Coin) -> Result<Argument> {
-        self.obj(ObjectArg::ImmOrOwnedObject(coin.object_ref()))
+        Ok(self.obj(ObjectArg::ImmOrOwnedObject(coin.object_ref()))?)
     }
 
     pub fn coins_to_args(&mut self, coins: &[Coin]) -> Result<Vec<Argument>> {
-        coins
+        Ok(coins
             .iter()
             .map(|item| self.obj(ObjectArg::ImmOrOwnedObject(item.object_ref())))
-            .collect::<Result<Vec<Argument>>>()
+            .collect::<anyhow::Result<Vec<Argument>>>()?)
     }
 
     pub async fn get_split_coin_for_tx(
@@ -276,9 +293,10 @@ impl Ptb {
         splits: &[u64],
         coin_type: &str,
         inspect_transaction: Option<bool>,
+        client: &SuiClient,
     ) -> Result<Argument> {
-        let (_object_ids, coins, _balance) =
-            get_coin_object_ids_by_amount(account, amount, &coin_type).await?;
+        let (_object_ids, coins, balance) =
+            get_coin_object_ids_by_amount(client, account, amount, &coin_type).await?;
 
         if let Some(main_coin) = coins.get(0) {
             if coin_type == SUI_TYPE {
@@ -308,7 +326,10 @@ impl Ptb {
             return Ok(coins_arg);
         }
 
-        Err(anyhow!("No valid coin object IDs found"))
+        Err(SevenKError::InsufficientBalance {
+            needed: amount,
+            available: balance,
+        })
     }
 
     pub fn clock(&mut self) -> Result<Argument> {
@@ -318,7 +339,17 @@ impl Ptb {
             mutable: false,
         };
 
-        self.obj(obj_arg)
+        Ok(self.obj(obj_arg)?)
+    }
+
+    pub fn sui_system_state(&mut self) -> Result<Argument> {
+        let obj_arg = ObjectArg::SharedObject {
+            id: SUI_SYSTEM_STATE_OBJECT_ID,
+            initial_shared_version: SUI_SYSTEM_STATE_OBJECT_SHARED_VERSION,
+            mutable: true,
+        };
+
+        Ok(self.obj(obj_arg)?)
     }
 }
 
@@ -339,7 +370,10 @@ pub fn get_coins_greater_than_amount(amount: u64, coins: &Vec<Coin>) -> Result<V
     if collected_amount >= amount {
         Ok(coins_with_balance)
     } else {
-        Err(anyhow::anyhow!("Insufficient balance"))
+        Err(SevenKError::InsufficientBalance {
+            needed: amount,
+            available: collected_amount,
+        })
     }
 }
 
@@ -413,13 +447,19 @@ impl ObjectRefFetcher for SuiClient {
             .await?;
 
         if let Some(error) = object.error {
-            return Err(anyhow!(error));
+            return Err(SevenKError::ObjectFetch {
+                object_id: object_str.to_string(),
+                reason: error.to_string(),
+            });
         }
 
         if let Some(data) = object.data {
             Ok(data.object_ref())
         } else {
-            Err(anyhow!("No data found for object {:?}", object_id))
+            Err(SevenKError::ObjectFetch {
+                object_id: object_str.to_string(),
+                reason: "no data returned".to_string(),
+            })
         }
     }
 
@@ -428,31 +468,28 @@ impl ObjectRefFetcher for SuiClient {
     }
 
     async fn shared_obj_mut(&self, object_id: &str) -> Result<ObjectArg> {
-        Ok(self.object_ref(object_id).await?.shared_obj(true))
+        ObjectRefCache::global()
+            .shared_obj(self, ObjectID::from_str(object_id)?, true)
+            .await
     }
 
     async fn shared_obj_imut(&self, object_id: &str) -> Result<ObjectArg> {
-        Ok(self.object_ref(object_id).await?.shared_obj(false))
+        ObjectRefCache::global()
+            .shared_obj(self, ObjectID::from_str(object_id)?, false)
+            .await
     }
 }
 
+// Shared objects need their `initial_shared_version`, which an `ObjectRef` does not carry; go
+// through `ObjectRefFetcher::shared_obj_mut`/`shared_obj_imut` for those.
 pub trait ObjectArgExt {
     fn owned_obj(&self) -> ObjectArg;
-    fn shared_obj(&self, is_mut: bool) -> ObjectArg;
 }
 
 impl ObjectArgExt for ObjectRef {
     fn owned_obj(&self) -> ObjectArg {
         ObjectArg::ImmOrOwnedObject(*self)
     }
-
-    fn shared_obj(&self, is_mut: bool) -> ObjectArg {
-        ObjectArg::SharedObject {
-            id: self.0,
-            initial_shared_version: OBJECT_START_VERSION,
-            mutable: is_mut,
-        }
-    }
 }
 
 pub trait ArgumentExt {
@@ -465,7 +502,9 @@ impl ArgumentExt for Argument {
     fn get_slice(&self, idx: u16) -> Result<Argument> {
         match self {
             Argument::Result(idx_) => Ok(Argument::NestedResult(*idx_, idx)),
-            _ => Err(anyhow!("Expecting Argument to be a Result")),
+            _ => Err(SevenKError::Other(anyhow!(
+                "Expecting Argument to be a Result"
+            ))),
         }
     }
 
@@ -60,6 +60,11 @@ pub fn format_balance(balance: u64, decimals: u32) -> u64 {
     balance / divisor
 }
 
+/// `balance` in whole tokens, keeping the fractional part `format_balance` truncates.
+pub fn balance_to_decimal(balance: u64, decimals: u32) -> f64 {
+    balance as f64 / 10_f64.powi(decimals as i32)
+}
+
 pub fn format_raw_balance(balance: u64, decimals: u32) -> u64 {
     balance.checked_mul(10_u64.pow(decimals)).unwrap()
 }
@@ -74,6 +79,12 @@ mod tests {
         assert_eq!(format_balance(1234567000000, 6), 1234567_u64);
     }
 
+    #[test]
+    fn test_balance_to_decimal() {
+        assert_eq!(balance_to_decimal(1_500_000, 6), 1.5);
+        assert_eq!(balance_to_decimal(2_345_678, 9), 0.002345678);
+    }
+
     #[test]
     fn test_format_raw_balance() {
         assert_eq!(format_raw_balance(1, 6), 1000000);